@@ -0,0 +1,16 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn main() {
+    let b = true;
+    Vassert(b);
+    let f = 1.5;
+    Vassert(f > 1.0);
+    let g = 2.0f32;
+    Vassert(g - f == 0.5);
+    let c = 'a';
+    Vassert(c == 'a');
+    let n = 5u8;
+    let byte = b'A';
+    Vassert(byte - n == 60);
+}
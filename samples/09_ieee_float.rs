@@ -0,0 +1,14 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+// flags: --ieee-float
+fn main() {
+    let x = 0.1 + 0.2;
+    Vassert(x != 0.3);
+    let y = Vrand_float::<f64>();
+    Vassume(y == y);
+    Vassert(y * 0.0 == 0.0 || y * 0.0 != y * 0.0);
+    let z = -0.0f32;
+    Vassert(z == 0.0);
+    Vassert(7.5 % -2.0 == 1.5);
+}
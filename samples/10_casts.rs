@@ -0,0 +1,24 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+// run with and without `--bitvector-int` / `--ieee-float`
+fn main() {
+    let x = 300i32;
+    Vassert(x as u8 == 44);
+    Vassert(x as i8 == 44);
+    let y = -1i32;
+    Vassert(y as u32 == 4294967295);
+    Vassert(y as i64 == -1);
+    Vassert((200u8 as i8) == -56);
+    Vassert(true as i32 == 1);
+    Vassert('a' as u8 == 97);
+    Vassert(65u8 as char == 'A');
+    Vassert(-1.9f64 as i32 == -1);
+    Vassert(1e10 as i32 == 2147483647);
+    Vassert(-5.0 as u8 == 0);
+    Vassert(3i32 as f64 == 3.0);
+    let r = Vrand_int::<i32>();
+    let n = r as u8;
+    Vassert(n as u32 <= 255);
+    Vassert((n as i8) as u8 == n);
+}
@@ -0,0 +1,45 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn inc(x: &mut i32) {
+    *x += 1;
+}
+
+fn reset(p: &mut Point) {
+    p.x = 0;
+    inc(&mut p.y);
+}
+
+fn sum(p: &Point) -> i32 {
+    p.x + p.y
+}
+
+fn main() {
+    let mut a = Vrand_int::<i32>();
+    let old = a;
+    inc(&mut a);
+    Vassert(a == old + 1);
+
+    let mut p = Point { x: 3, y: 4 };
+    reset(&mut p);
+    Vassert(p.x == 0 && p.y == 5);
+    Vassert(sum(&p) == 5);
+
+    let mut arr = [1, 2, 3];
+    inc(&mut arr[1]);
+    Vassert(arr[1] == 3 && arr[0] == 1);
+
+    let mut b = 10;
+    let c = Vrand_bool::<bool>();
+    let r = if c { &mut a } else { &mut b };
+    *r = 7;
+    Vassert(c || b == 7);
+    Vassert(!c || a == 7);
+    let s = &b;
+    Vassert(*s == b);
+}
@@ -0,0 +1,42 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+struct Counter {
+    count: i32,
+    step: i32,
+}
+
+impl Counter {
+    const START: i32 = 0;
+
+    fn new(step: i32) -> Counter {
+        Counter {
+            count: Self::START,
+            step,
+        }
+    }
+
+    fn get(&self) -> i32 {
+        self.count
+    }
+
+    fn tick(&mut self) {
+        self.count += self.step;
+    }
+
+    fn into_count(self) -> i32 {
+        self.count
+    }
+}
+
+fn main() {
+    let step = Vrand_int::<i32>();
+    Vassume(step > 0 && step < 100);
+    let mut c = Counter::new(step);
+    Vassert(c.get() == 0);
+    c.tick();
+    c.tick();
+    Vassert(c.get() == 2 * step);
+    Counter::tick(&mut c);
+    Vassert(c.into_count() == 3 * step);
+}
@@ -0,0 +1,67 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+trait Shape {
+    fn area(&self) -> i32;
+}
+
+struct Square {
+    side: i32,
+}
+
+struct Rect {
+    w: i32,
+    h: i32,
+}
+
+impl Shape for Square {
+    fn area(&self) -> i32 {
+        self.side * self.side
+    }
+}
+
+impl Shape for Rect {
+    fn area(&self) -> i32 {
+        self.w * self.h
+    }
+}
+
+struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+fn choose<T>(c: bool, a: T, b: T) -> T {
+    if c {
+        a
+    } else {
+        b
+    }
+}
+
+fn swap<T: Copy>(p: &mut Pair<T>) {
+    let tmp = p.first;
+    p.first = p.second;
+    p.second = tmp;
+}
+
+fn total_area<S: Shape>(s: &S) -> i32 {
+    s.area()
+}
+
+fn main() {
+    let c = Vrand_bool::<bool>();
+    let x = choose(c, 1, 2);
+    Vassert(x == 1 || x == 2);
+    let f = choose(c, 1.5, 2.5);
+    Vassert(c || f == 2.5);
+
+    let mut p = Pair { first: 3u8, second: 4u8 };
+    swap(&mut p);
+    Vassert(p.first == 4 && p.second == 3);
+
+    let s = Square { side: 3 };
+    let r = Rect { w: 2, h: 5 };
+    Vassert(total_area(&s) == 9);
+    Vassert(total_area(&r) == 10);
+}
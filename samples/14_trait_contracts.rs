@@ -0,0 +1,97 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+trait Shape {
+    fn area(&self) -> i32 {
+        Vensures(Vresult::<i32>() >= 0);
+        Vresult()
+    }
+
+    fn scale(&self, k: i32) -> i32 {
+        Vrequires(k > 0);
+        Vensures(Vresult::<i32>() >= k);
+        Vresult()
+    }
+}
+
+trait Counter {
+    fn bump(&mut self) -> i32 {
+        Vensures(Vresult::<i32>() > 0);
+        Vresult()
+    }
+}
+
+struct Square {
+    side: i32,
+}
+
+struct Rect {
+    w: i32,
+    h: i32,
+}
+
+impl Shape for Square {
+    fn area(&self) -> i32 {
+        self.side * self.side
+    }
+
+    fn scale(&self, k: i32) -> i32 {
+        k + self.side * self.side
+    }
+}
+
+impl Shape for Rect {
+    fn area(&self) -> i32 {
+        if self.w < 0 || self.h < 0 {
+            0
+        } else {
+            self.w * self.h
+        }
+    }
+
+    fn scale(&self, k: i32) -> i32 {
+        k * (1 + self.area())
+    }
+}
+
+struct Ticks {
+    n: i32,
+}
+
+impl Counter for Ticks {
+    fn bump(&mut self) -> i32 {
+        if self.n < 0 {
+            self.n = 0;
+        }
+        self.n += 1;
+        self.n
+    }
+}
+
+fn measure(s: &dyn Shape) -> i32 {
+    s.scale(2)
+}
+
+fn non_negative(x: i32) -> i32 {
+    Vrequires(x > -100);
+    Vensures(Vresult::<i32>() >= 0);
+    if x < 0 {
+        -x
+    } else {
+        x
+    }
+}
+
+fn main() {
+    let sq = Square { side: 3 };
+    Vassert(sq.area() == 9);
+    let r = Rect { w: 2, h: 5 };
+    Vassert(r.scale(1) == 11);
+    let s = &sq as &dyn Shape;
+    Vassert(s.area() >= 0);
+    Vassert(measure(&r) >= 2);
+    Vassert(non_negative(-5) >= 0);
+    let mut t = Ticks { n: 0 };
+    let first = t.bump();
+    Vassert(first == 1 && t.bump() == 2);
+}
@@ -0,0 +1,46 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn apply<F: Fn(i32) -> i32>(f: F, x: i32) -> i32 {
+    f(x)
+}
+
+fn apply_twice<F: FnMut()>(mut f: F) {
+    f();
+    f();
+}
+
+fn consume<F: FnOnce() -> i32>(f: F) -> i32 {
+    f()
+}
+
+fn main() {
+    let a = Vrand_int::<i32>();
+    Vassume(a > 0 && a < 1000);
+
+    let add = |x: i32| x + a;
+    Vassert(add(1) == a + 1);
+    Vassert(apply(add, 2) == a + 2);
+    Vassert(apply(|x| x * 2, a) == 2 * a);
+
+    let mut count = 0;
+    let mut inc = || count += a;
+    inc();
+    apply_twice(&mut inc);
+    Vassert(count == 3 * a);
+
+    let mut b = 5;
+    let get_b = move || b;
+    b = 7;
+    Vassert(get_b() == 5 && b == 7);
+
+    let mut total = 0;
+    let mut acc = move |x: i32| {
+        total += x;
+        total
+    };
+    acc(3);
+    Vassert(acc(4) == 7 && total == 0);
+
+    Vassert(consume(move || b + a) == a + 7);
+}
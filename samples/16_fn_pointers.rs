@@ -0,0 +1,37 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+fn negate(x: i32) -> i32 {
+    -x
+}
+
+fn apply(f: fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+fn apply_generic<F: Fn(i32) -> i32>(f: F, x: i32) -> i32 {
+    f(x)
+}
+
+fn main() {
+    let a = Vrand_int::<i32>();
+    Vassume(a > -1000 && a < 1000);
+
+    let f = double as fn(i32) -> i32;
+    Vassert(f(a) == 2 * a);
+    Vassert(apply(double, a) == 2 * a);
+
+    let g = if a > 0 { double as fn(i32) -> i32 } else { negate };
+    Vassert(g(a) >= 0);
+    Vassert(apply(g, a) >= 0);
+
+    Vassert(apply_generic(negate, a) == -a);
+    Vassert(apply_generic(f, a) == 2 * a);
+
+    let inc = (|x: i32| x + 1) as fn(i32) -> i32;
+    Vassert(apply(inc, a) == a + 1);
+}
@@ -0,0 +1,34 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn main() {
+    let a = Vrand_int::<i32>();
+    Vassume(a > -1000 && a < 1000);
+
+    Vassert(a.abs() >= 0);
+    Vassert(a.signum() * a.abs() == a);
+    Vassert(a.is_negative() == (a < 0));
+    Vassert(a.rem_euclid(7) >= 0);
+    Vassert(a.unsigned_abs() as i32 == a.abs());
+
+    let c = Vrand_int::<i64>();
+    Vassume(c >= 48 && c < 58);
+    Vassert((c as u8).is_ascii_digit());
+
+    // `count_ones` is an intrinsic without MIR: the result is abstracted and a warning
+    // is printed.
+    let m = c.count_ones();
+    Vassume(m <= 64);
+    Vassert(m < 65);
+
+    // `Option::map` calls back into the analyzed crate, so the variables its closure
+    // writes are abstracted as well; the ones it only reads are kept.
+    let mut calls = 0;
+    let step = 3;
+    let d = Some(c).map(|x| {
+        calls += 1;
+        x + step
+    });
+    Vassume(d.is_some() && calls <= 1);
+    Vassert(step == 3);
+}
@@ -0,0 +1,34 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn main() {
+    let a = Vrand_int::<i32>();
+    let b = Vrand_int::<i32>();
+
+    Vassert(a.min(b) <= a && a.min(b) <= b);
+    Vassert(std::cmp::max(a, b) >= a);
+    Vassert(a.clamp(-10, 10) <= 10);
+    Vassert(a.saturating_sub(b) <= 2147483647);
+    Vassert(a.wrapping_mul(0) == 0);
+
+    Vassume(a > -1000 && a < 1000);
+    Vassert(a.abs() >= 0);
+    Vassert(a.is_positive() == (a > 0));
+    Vassert(a.pow(2) >= 0);
+
+    let max = Vrand_int::<i32>();
+    Vassume(max == 2147483647);
+    Vassert(max.checked_add(1).is_none());
+    Vassert(max.saturating_add(1) == max);
+    Vassert(max.wrapping_add(1) < 0);
+    Vassert(a.checked_add(1).unwrap_or(0) == a + 1);
+
+    let mut calls = 0;
+    let positive = (a > 0).then(|| {
+        calls += 1;
+        a
+    });
+    Vassert(positive.is_some() == (a > 0));
+    Vassert(calls <= 1);
+    Vassert((a > 0).then_some(a).unwrap_or(1) > 0);
+}
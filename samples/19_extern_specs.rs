@@ -0,0 +1,31 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+// Specs standing for functions of other crates whose bodies cannot be analyzed. The
+// argument of `Vextern_spec` is the path of the function; the parameters and the contract
+// are those of the spec.
+#[allow(dead_code)]
+fn count_ones_spec(_x: i64) -> u32 {
+    Vextern_spec("core::num::<impl i64>::count_ones");
+    Vensures(Vresult::<u32>() <= 64);
+    0
+}
+
+#[allow(dead_code)]
+fn leading_zeros_spec(x: u32) -> u32 {
+    Vextern_spec("core::num::<impl u32>::leading_zeros");
+    Vrequires(x > 0);
+    Vensures(Vresult::<u32>() < 32);
+    0
+}
+
+fn main() {
+    let a = Vrand_int::<i64>();
+    let m = a.count_ones();
+    Vassert(m < 65);
+
+    let b = Vrand_int::<i64>();
+    Vassume(b > 0 && b < 1000);
+    let z = (b as u32).leading_zeros();
+    Vassert(z <= 31);
+}
@@ -0,0 +1,47 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn half(x: i32) -> i32 {
+    assert!(x % 2 == 0, "x must be even");
+    x / 2
+}
+
+fn main() {
+    let a = Vrand_int::<i32>();
+    Vassume(a > 0 && a < 1000);
+
+    // Panics, including the ones of `assert!` and `unreachable!`, must be unreachable.
+    assert!(a != 0);
+    if a > 1000 {
+        panic!("out of range");
+    }
+    if a < 0 {
+        unreachable!();
+    }
+    let b = half(a * 2);
+    Vassert(b == a);
+
+    // `unwrap` and `expect` of other crates are checked on their MIR bodies.
+    let c = a.checked_add(1).unwrap();
+    Vassert(c == a + 1);
+    let d = if a > 10 { Some(a) } else { None };
+    if a > 20 {
+        let e = d.expect("a is greater than 10");
+        Vassert(e > 20);
+    }
+
+    // Those of `Result` format the error through `dyn Debug`, so they are modelled.
+    let r: Result<i32, i32> = if a > 5 { Ok(a) } else { Err(a) };
+    if a > 5 {
+        Vassert(r.unwrap() == a);
+    } else {
+        Vassert(r.unwrap_err() <= 5);
+    }
+
+    // Array indexing is bounds checked.
+    let mut arr = [0, 1, 2, 3];
+    let i = Vrand_int::<i64>();
+    Vassume(i >= 0 && i < 4);
+    arr[i as usize] = 5;
+    Vassert(arr[i as usize] == 5);
+}
@@ -0,0 +1,41 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn stop() -> ! {
+    std::process::exit(1)
+}
+
+fn positive(x: i32) -> i32 {
+    if x <= 0 {
+        stop();
+    }
+    x
+}
+
+fn main() {
+    let a = Vrand_int::<i32>();
+
+    // Code after a diverging expression is unreachable, so only the paths that continue
+    // are checked.
+    if a < 10 {
+        std::process::exit(0);
+    }
+    Vassert(a >= 10);
+
+    if a > 100 {
+        stop();
+    }
+    Vassert(a <= 100);
+
+    if a == 50 {
+        loop {}
+    }
+    Vassert(a != 50);
+
+    let b = if a > 20 { a } else { std::process::exit(2) };
+    Vassert(b > 20);
+
+    let c = positive(a - 30);
+    Vassert(a > 30);
+    Vassert(c > 0);
+}
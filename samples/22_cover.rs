@@ -0,0 +1,19 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn main() {
+    let a = Vrand_int::<i32>();
+    Vassume(a > 0 && a < 100);
+
+    // Covers ask whether some execution reaches them with the condition holding.
+    Vcover(a == 42);
+    if a % 2 == 0 {
+        Vcover(a > 90);
+    }
+    let b = a * 3;
+    Vcover(b == 30);
+
+    // Unreachable covers are reported as warnings: `b` is a multiple of 3.
+    Vcover(b == 31);
+    Vassert(b > 0);
+}
@@ -0,0 +1,17 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn main() {
+    let a = Vrand_int::<i32>();
+    Vassume(a > 0);
+    Vassert(a >= 1);
+
+    // A branch that no execution takes: the assertion in it holds vacuously.
+    if a < 0 {
+        Vassert(a == 5);
+    }
+
+    // An assumption contradicting the earlier ones makes every later assertion vacuous.
+    Vassume(a < 0);
+    Vassert(a == 7);
+}
@@ -0,0 +1,25 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn digit_value(c: i32) -> i32 {
+    if c >= 48 && c < 58 {
+        c - 48
+    } else {
+        Vunreachable()
+    }
+}
+
+fn main() {
+    let a = Vrand_int::<i32>();
+    Vassume(a >= 0 && a < 10);
+
+    // Impossible branches are proved unreachable.
+    if a > 20 {
+        Vunreachable();
+    }
+    let b = if a < 10 { a * 2 } else { Vunreachable() };
+    Vassert(b < 20);
+
+    let d = digit_value(a + 48);
+    Vassert(d == a);
+}
@@ -0,0 +1,51 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn positive() -> i32 {
+    let x = Vrand_int::<i32>();
+    Vassume(x > 0 && x < 1000);
+    x
+}
+
+fn is_small(x: i32) -> bool {
+    Vassert(x != 0);
+    Vassume(x < 1000);
+    x < 10
+}
+
+fn check_large(x: i32) {
+    if x > 5 {
+        Vassert(x > 3);
+    }
+}
+
+fn main() {
+    let a = Vrand_int::<i32>();
+    let b = Vrand_int::<i32>();
+
+    // Assumptions made in a branch hold under its condition after the merge.
+    if a > 0 {
+        Vassume(b == 1);
+    } else {
+        Vassume(b == 2);
+    }
+    Vassert(b == 1 || b == 2);
+    Vassert(a <= 0 || b == 1);
+
+    // Including the ones of inlined callees.
+    let c = if a > 0 { positive() } else { 1 };
+    Vassert(c > 0);
+
+    // Calls in conditions are checked on the path before the branch, and their
+    // assumptions are kept.
+    Vassume(a != 0);
+    if is_small(a) {
+        Vassert(a < 10);
+    }
+    Vassert(a < 1000);
+
+    // Assertions in callees inside branches are checked under both conditions.
+    if a > 0 {
+        check_large(a);
+    }
+}
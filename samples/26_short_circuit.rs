@@ -0,0 +1,37 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn check(x: i32) -> bool {
+    x < 100
+}
+
+fn is_even(x: i32) -> bool {
+    Vassert(x > 0);
+    x % 2 == 0
+}
+
+fn main() {
+    let x = Vrand_int::<i32>();
+    Vassume(x > -1000 && x < 1000);
+
+    // The right operand is only evaluated when the left one holds.
+    let a = x != 0 && check(10 / x);
+    Vassert(a == (x != 0));
+
+    // Or when it does not hold, for `||`.
+    let b = x == 0 || check(50 % x);
+    Vassert(b);
+
+    // Assertions of inlined callees are checked under the left operand.
+    let c = x > 0 && is_even(x);
+    Vassert(!c || x >= 2);
+
+    // Assignments on the right-hand side only happen on its path.
+    let mut y = 0;
+    let d = x > 10 || {
+        y = 1;
+        true
+    };
+    Vassert(d);
+    Vassert(y == 0 || x <= 10);
+}
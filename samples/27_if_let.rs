@@ -0,0 +1,73 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+enum Shape {
+    Circle(i32),
+    Rect { w: i32, h: i32 },
+    Empty,
+}
+
+fn area(s: Shape) -> i32 {
+    if let Shape::Rect { w, h } = s {
+        w * h
+    } else if let Shape::Circle(r) = s {
+        3 * r * r
+    } else {
+        0
+    }
+}
+
+fn halve(n: i32) -> Option<i32> {
+    if n > 0 {
+        Some(n / 2)
+    } else {
+        None
+    }
+}
+
+fn main() {
+    let x = Vrand_int::<i32>();
+    Vassume(x > 0 && x < 100);
+
+    // The bindings of `if let` hold where the pattern matches.
+    let opt = if x > 50 { Some(x) } else { None };
+    if let Some(n) = opt {
+        Vassert(n > 50);
+    } else {
+        Vassert(x <= 50);
+    }
+    let v = if let Some(n) = opt { n } else { 0 };
+    Vassert(v == 0 || v > 50);
+
+    // Nested patterns.
+    let pair = Some((x, x + 1));
+    if let Some((a, b)) = pair {
+        Vassert(b == a + 1);
+    } else {
+        Vunreachable();
+    }
+    Vassert(area(Shape::Rect { w: x, h: 2 }) == 2 * x);
+    Vassert(area(Shape::Circle(1)) == 3);
+    Vassert(area(Shape::Empty) == 0);
+
+    // The else block of `let-else` diverges.
+    let Some(half) = halve(x) else {
+        Vunreachable();
+        return;
+    };
+    Vassert(half * 2 <= x);
+
+    // A `while let` loop ends where its pattern does not match.
+    let mut it = opt;
+    while let Some(k) = it {
+        it = if k > 0 { Some(k - 1) } else { None };
+    }
+    if let Some(_) = it {
+        Vunreachable();
+    }
+
+    let Some(m) = opt else {
+        return;
+    };
+    Vassert(m > 50);
+}
@@ -0,0 +1,93 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+enum Op {
+    Add(i32, i32),
+    Neg(i32),
+    Nop,
+}
+
+fn eval(op: Op) -> i32 {
+    match op {
+        Op::Add(a, b) => a + b,
+        Op::Neg(a) => -a,
+        Op::Nop => 0,
+    }
+}
+
+fn classify(n: i32) -> i32 {
+    match n {
+        -1000..=-1 => -1,
+        0 => 0,
+        1 | 2 | 3 => 1,
+        x @ 4..=9 => x,
+        _ => 10,
+    }
+}
+
+fn sign(n: i32) -> i32 {
+    match n {
+        x if x > 0 => 1,
+        x if x < 0 => -1,
+        _ => 0,
+    }
+}
+
+fn sum((a, b): (i32, i32)) -> i32 {
+    a + b
+}
+
+fn first_last(arr: [i32; 4]) -> i32 {
+    let [first, .., last] = arr;
+    first + last
+}
+
+fn main() {
+    let x = Vrand_int::<i32>();
+    Vassume(x > -100 && x < 100);
+
+    // Variant patterns.
+    Vassert(eval(Op::Add(x, 1)) == x + 1);
+    Vassert(eval(Op::Neg(x)) == -x);
+    Vassert(eval(Op::Nop) == 0);
+
+    // Literals, ranges, or-patterns and `@` bindings.
+    let c = classify(x);
+    Vassert(c >= -1 && c <= 10);
+    Vassert(x >= 0 || c == -1);
+    Vassert(!(x >= 4 && x <= 9) || c == x);
+    Vassert(classify(2) == 1);
+
+    // Guards.
+    let s = sign(x);
+    Vassert(s * x >= 0);
+
+    // Tuple and array patterns in parameters and `let`.
+    Vassert(sum((x, 2)) == x + 2);
+    Vassert(first_last([x, 0, 0, 5]) == x + 5);
+    let (p, q) = (x, x * 2);
+    Vassert(q == 2 * p);
+
+    // An or-pattern binds a name in every alternative.
+    let r = match Op::Neg(x) {
+        Op::Add(v, _) | Op::Neg(v) => v,
+        Op::Nop => 0,
+    };
+    Vassert(r == x);
+
+    // Character ranges.
+    let ch = 'q';
+    let kind = match ch {
+        'a'..='z' => 1,
+        '0'..='9' => 2,
+        _ => 3,
+    };
+    Vassert(kind == 1);
+
+    // Matches as statements, and `assert_eq!`.
+    match x {
+        0 => Vassert(s == 0),
+        _ => {}
+    }
+    assert_eq!(sum((x, 1)), x + 1);
+}
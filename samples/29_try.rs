@@ -0,0 +1,61 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+enum ParseError {
+    Empty,
+    Negative(i32),
+}
+
+struct AppError {
+    code: i32,
+}
+
+impl From<ParseError> for AppError {
+    fn from(e: ParseError) -> Self {
+        match e {
+            ParseError::Empty => AppError { code: 1 },
+            ParseError::Negative(_) => AppError { code: 2 },
+        }
+    }
+}
+
+fn incr(a: Option<i32>) -> Option<i32> {
+    Some(a? + 1)
+}
+
+fn add(a: Option<i32>, b: Option<i32>) -> Option<i32> {
+    Some(a? + b?)
+}
+
+fn check(n: i32) -> Result<i32, ParseError> {
+    if n == 0 {
+        Err(ParseError::Empty)
+    } else if n < 0 {
+        Err(ParseError::Negative(n))
+    } else {
+        Ok(n)
+    }
+}
+
+fn run(n: i32) -> Result<i32, AppError> {
+    let v = check(n)?;
+    Vassert(v > 0);
+    Ok(100 / v)
+}
+
+fn main() {
+    let x = Vrand_int::<i32>();
+    Vassume(x > 0 && x < 100);
+    Vassert(incr(Some(x)) == Some(x + 1));
+    Vassert(incr(None).is_none());
+    Vassert(add(Some(x), Some(1)) == Some(x + 1));
+    Vassert(add(Some(x), None).is_none());
+    Vassert(add(None, Some(x)).is_none());
+
+    let n = Vrand_int::<i32>();
+    Vassume(n > -100 && n < 100);
+    match run(n) {
+        Ok(q) => Vassert(n > 0 && q == 100 / n),
+        Err(e) => Vassert((n == 0 && e.code == 1) || (n < 0 && e.code == 2)),
+    }
+}
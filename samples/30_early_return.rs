@@ -0,0 +1,92 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+fn classify(n: i32) -> i32 {
+    if n < 0 {
+        return -1;
+    }
+    if n == 0 {
+        return 0;
+    }
+    1
+}
+
+fn clamp(x: i32, lo: i32, hi: i32) -> i32 {
+    if x < lo {
+        return lo;
+    } else if x > hi {
+        return hi;
+    }
+    x
+}
+
+fn ratio(a: i32, b: i32) -> i32 {
+    if a > 0 {
+        if b > a {
+            return b;
+        }
+        return a;
+    }
+    if b == 0 {
+        return 0;
+    }
+    // Only reached where `b` is nonzero.
+    100 / b
+}
+
+fn bump(counter: &mut i32, n: i32) {
+    if n <= 0 {
+        return;
+    }
+    *counter += n;
+}
+
+fn sign(o: Option<i32>) -> i32 {
+    let Some(x) = o else {
+        return 0;
+    };
+    if x == 0 {
+        return 0;
+    }
+    if x > 0 {
+        1
+    } else {
+        -1
+    }
+}
+
+fn pick(n: i32) -> i32 {
+    match n {
+        0 => return 10,
+        1 => {}
+        _ => return 20,
+    }
+    30
+}
+
+fn main() {
+    let n = Vrand_int::<i32>();
+    Vassume(n > -100 && n < 100);
+    let c = classify(n);
+    Vassert((n < 0 && c == -1) || (n == 0 && c == 0) || (n > 0 && c == 1));
+    let v = clamp(n, -10, 10);
+    Vassert(v >= -10 && v <= 10);
+    Vassert(n < -10 || n > 10 || v == n);
+
+    let m = Vrand_int::<i32>();
+    Vassume(m > -100 && m < 100);
+    let r = ratio(n, m);
+    Vassert(n <= 0 || r == n || r == m);
+    Vassert(n > 0 || m != 0 || r == 0);
+
+    let mut counter = 5;
+    bump(&mut counter, n);
+    Vassert(n > 0 || counter == 5);
+    Vassert(n <= 0 || counter == 5 + n);
+
+    Vassert(sign(None) == 0);
+    Vassert(sign(Some(n)) == classify(n));
+
+    let p = pick(n);
+    Vassert((n == 0 && p == 10) || (n == 1 && p == 30) || (n != 0 && n != 1 && p == 20));
+}
@@ -0,0 +1,84 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+const LIMIT: i32 = 10;
+const DOUBLE_LIMIT: i32 = LIMIT * 2;
+static SCALE: i32 = 3;
+static ENABLED: bool = true;
+
+struct Buffer;
+
+impl Buffer {
+    const CAPACITY: u32 = 64;
+}
+
+trait Bounded {
+    const MAX: i32;
+    const MIN: i32 = 0;
+
+    fn clamp(v: i32) -> i32 {
+        if v > Self::MAX {
+            Self::MAX
+        } else if v < Self::MIN {
+            Self::MIN
+        } else {
+            v
+        }
+    }
+}
+
+struct Percent;
+
+impl Bounded for Percent {
+    const MAX: i32 = 100;
+}
+
+struct Signed;
+
+impl Bounded for Signed {
+    const MAX: i32 = 127;
+    const MIN: i32 = -128;
+}
+
+fn limited(x: i32) -> bool {
+    x <= LIMIT
+}
+
+fn first_or_zero<const N: usize>(arr: [i32; N]) -> i32 {
+    if N > 0 {
+        arr[0]
+    } else {
+        0
+    }
+}
+
+fn len<const N: usize>(_arr: [i32; N]) -> usize {
+    N
+}
+
+fn main() {
+    let x = Vrand_int::<i32>();
+    Vassume(x > 0 && x < LIMIT);
+    Vassert(limited(x));
+    Vassert(x * 2 < DOUBLE_LIMIT);
+    Vassert(x * SCALE < 30);
+    Vassert(ENABLED);
+
+    let n = Vrand_int::<i64>();
+    Vassume(n >= 0 && n < u32::MAX as i64);
+    let m = n as u32;
+    Vassert(m + 1 > m);
+    Vassert(Buffer::CAPACITY == 64);
+    Vassert(i32::MIN < 0 && i64::MAX > 0);
+    Vassert(u8::MAX as i32 == 255);
+
+    let v = Vrand_int::<i32>();
+    let p = Percent::clamp(v);
+    Vassert(p >= 0 && p <= 100);
+    let s = Signed::clamp(v);
+    Vassert(s >= -128 && s <= 127);
+
+    Vassert(len([1, 2, 3]) == 3);
+    Vassert(len([0; 5]) == 5);
+    Vassert(first_or_zero([7, 8]) == 7);
+}
@@ -0,0 +1,86 @@
+extern crate verify_modules;
+use verify_modules::*;
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static mut COUNT: i32 = 0;
+static mut LIMITS: (i32, i32) = (0, 100);
+static HITS: AtomicUsize = AtomicUsize::new(0);
+static READY: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static DEPTH: Cell<u32> = Cell::new(0);
+}
+
+fn bump() -> i32 {
+    unsafe {
+        COUNT += 1;
+        COUNT
+    }
+}
+
+fn hit(counter: &AtomicUsize) -> usize {
+    counter.fetch_add(1, Ordering::SeqCst)
+}
+
+fn enter() -> u32 {
+    DEPTH.with(|depth| {
+        depth.set(depth.get() + 1);
+        depth.get()
+    })
+}
+
+fn main() {
+    Vassert(bump() == 1);
+    Vassert(bump() == 2);
+    let c = unsafe { COUNT };
+    Vassert(c == 2);
+
+    let x = Vrand_int::<i32>();
+    Vassume(x > 0 && x < 10);
+    unsafe {
+        if x > 5 {
+            COUNT = x;
+        }
+        LIMITS.1 = 50;
+    }
+    let c = unsafe { COUNT };
+    Vassert(c == 2 || c > 5);
+    Vassert(unsafe { LIMITS.0 == 0 && LIMITS.1 == 50 });
+
+    Vassert(HITS.load(Ordering::SeqCst) == 0);
+    Vassert(hit(&HITS) == 0);
+    HITS.fetch_add(2, Ordering::Relaxed);
+    Vassert(HITS.load(Ordering::SeqCst) == 3);
+    Vassert(HITS.swap(10, Ordering::SeqCst) == 3);
+    Vassert(HITS.compare_exchange(10, 11, Ordering::SeqCst, Ordering::SeqCst).is_ok());
+    match HITS.compare_exchange(10, 12, Ordering::SeqCst, Ordering::SeqCst) {
+        Ok(_) => Vassert(false),
+        Err(current) => Vassert(current == 11),
+    }
+    Vassert(HITS.fetch_max(20, Ordering::SeqCst) == 11);
+    Vassert(HITS.load(Ordering::SeqCst) == 20);
+
+    Vassert(!READY.load(Ordering::SeqCst));
+    READY.store(true, Ordering::Release);
+    Vassert(READY.load(Ordering::Acquire));
+
+    let local = AtomicUsize::new(5);
+    Vassert(hit(&local) == 5);
+    Vassert(local.into_inner() == 6);
+
+    let cell = Cell::new(1);
+    cell.set(cell.get() + 1);
+    Vassert(cell.replace(7) == 2);
+    Vassert(cell.get() == 7);
+
+    Vassert(enter() == 1);
+    Vassert(enter() == 2);
+
+    // `count_ones` is not analyzed, so the statics may have changed by the time it returns.
+    let ones = 7u32.count_ones();
+    let c = unsafe { COUNT };
+    Vassume(ones == 3 && c == 3);
+    Vassert(bump() == 4);
+}
@@ -3,48 +3,80 @@ use rustc_middle::mir::{BinOp, UnOp};
 use rustc_middle::thir::LocalVarId;
 use rustc_middle::thir::LogicalOp;
 use rustc_middle::ty::TyCtxt;
-use rustc_middle::ty::{Ty, TyKind};
-use rustc_span::def_id::LocalDefId;
+use rustc_middle::ty::{GenericArgsRef, Ty, TyKind};
+use rustc_span::def_id::{DefId, LocalDefId};
 
 use std::boxed::Box;
+use std::cell::RefCell;
 use std::collections::HashMap as Map;
+use std::collections::HashSet;
 use std::iter::Peekable;
 use std::rc::Rc;
 
+use crate::analyze::place::Place;
 use crate::thir::rthir::*;
 mod annotate;
+mod cast;
+mod closure;
+mod contract;
 pub mod core;
+pub mod encoding;
 mod env;
 mod expr;
+mod fn_ptr;
+mod global;
 mod lir;
+mod mir;
+mod model;
+mod panic;
+mod pattern;
+mod place;
 mod util;
 
-pub use {core::AnalysisError, env::Env, expr::*, lir::*};
+pub use {core::AnalysisError, encoding::Encoding, env::Env, expr::*, lir::*};
 
 pub fn analyze<'tcx>(
     main_id: LocalDefId,
     fn_map: Map<LocalDefId, Rc<RThir<'tcx>>>,
     tcx: TyCtxt<'tcx>,
+    encoding: Encoding,
 ) -> Result<(), AnalysisError> {
-    Analyzer::run(main_id, fn_map, tcx)
+    Analyzer::run(main_id, fn_map, tcx, encoding)
 }
 
 struct Analyzer<'tcx> {
     fn_map: Map<LocalDefId, Rc<RThir<'tcx>>>,
+    /// The generic functions and closures reduced again for the arguments of a call.
+    instances: RefCell<Map<(LocalDefId, GenericArgsRef<'tcx>), Rc<RThir<'tcx>>>>,
+    /// The functions and trait methods whose contract could not be checked; their
+    /// postconditions are not assumed at calls.
+    unchecked_contracts: RefCell<HashSet<DefId>>,
     tcx: TyCtxt<'tcx>,
+    encoding: Encoding,
 }
 
 impl<'tcx> Analyzer<'tcx> {
-    pub fn new(fn_map: Map<LocalDefId, Rc<RThir<'tcx>>>, tcx: TyCtxt<'tcx>) -> Self {
-        Self { fn_map, tcx }
+    pub fn new(
+        fn_map: Map<LocalDefId, Rc<RThir<'tcx>>>,
+        tcx: TyCtxt<'tcx>,
+        encoding: Encoding,
+    ) -> Self {
+        Self {
+            fn_map,
+            instances: RefCell::new(Map::new()),
+            unchecked_contracts: RefCell::new(HashSet::new()),
+            tcx,
+            encoding,
+        }
     }
 
     pub fn run(
         main_id: LocalDefId,
         fn_map: Map<LocalDefId, Rc<RThir<'tcx>>>,
         tcx: TyCtxt<'tcx>,
+        encoding: Encoding,
     ) -> Result<(), AnalysisError> {
-        let analyzer = Analyzer::new(fn_map, tcx);
+        let analyzer = Analyzer::new(fn_map, tcx, encoding);
         let main = analyzer.get_fn(main_id)?;
         analyzer.analyze_enter(main)
     }
@@ -12,6 +12,26 @@ impl<'tcx> Analyzer<'tcx> {
         Ok(AnalysisType::Other)
     }
 
+    pub fn analyze_cover(
+        &self,
+        args: Box<[Rc<RExpr<'tcx>>]>,
+        env: &mut Env<'tcx>,
+    ) -> Result<AnalysisType<'tcx>, AnalysisError> {
+        let cond = self.expr_to_const(args[0].clone(), env)?;
+        env.cover_z3(cond, args[0].span)?;
+        Ok(AnalysisType::Other)
+    }
+
+    /// `Vunreachable()`: no execution reaches the end of the current path.
+    pub fn unreachable_to_const(
+        &self,
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        env.verify_z3("false".to_string(), call.span)?;
+        self.zst_to_const(self.tcx.types.unit, env)
+    }
+
     pub fn analyze_assume(
         &self,
         args: Box<[Rc<RExpr<'tcx>>]>,
@@ -19,6 +39,16 @@ impl<'tcx> Analyzer<'tcx> {
     ) -> Result<AnalysisType<'tcx>, AnalysisError> {
         let constraint = self.expr_to_const(args[0].clone(), env)?;
         env.add_smt_command(constraint, args[0].clone());
+        if !env.is_feasible()? {
+            let assumption = env.path.pop().expect("the assumption was just added");
+            if env.is_feasible()? {
+                println!(
+                    "Warning: the assumption at {:?} contradicts the path it is on",
+                    args[0].span
+                );
+            }
+            env.path.push(assumption);
+        }
         Ok(AnalysisType::Other)
     }
 
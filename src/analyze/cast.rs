@@ -0,0 +1,199 @@
+use rustc_middle::ty::{FloatTy, Ty, TyKind};
+
+use std::cmp::Ordering;
+
+use crate::analyze::encoding::{Encoding, FloatEncoding, IntEncoding};
+use crate::analyze::*;
+
+impl<'tcx> Analyzer<'tcx> {
+    pub fn cast_to_const(
+        &self,
+        value: &str,
+        from: Ty<'tcx>,
+        to: Ty<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        if from == to {
+            return Ok(value.to_string());
+        }
+        let int_from = Encoding::int_width(from);
+        let int_to = Encoding::int_width(to);
+        match (from.kind(), to.kind()) {
+            (TyKind::Bool, _) if int_to.is_some() => {
+                let (width, _) = int_to.unwrap();
+                Ok(format!(
+                    "(ite {} {} {})",
+                    value,
+                    self.encoding.int_to_const(1, false, width),
+                    self.encoding.int_to_const(0, false, width)
+                ))
+            }
+            (_, TyKind::Float(float_ty)) if int_from.is_some() => {
+                let (width, signed) = int_from.unwrap();
+                Ok(self.int_to_float(value, width, signed, *float_ty))
+            }
+            (TyKind::Float(float_ty), _) if int_to.is_some() => {
+                let (width, signed) = int_to.unwrap();
+                Ok(self.float_to_int(value, *float_ty, width, signed))
+            }
+            (TyKind::Float(_), TyKind::Float(float_ty)) => match self.encoding.float {
+                FloatEncoding::Real => Ok(value.to_string()),
+                FloatEncoding::Ieee => {
+                    let (eb, sb) = Encoding::float_bits(*float_ty);
+                    Ok(format!("((_ to_fp {} {}) RNE {})", eb, sb, value))
+                }
+            },
+            _ if int_from.is_some() && int_to.is_some() => {
+                let (from_width, from_signed) = int_from.unwrap();
+                let (to_width, to_signed) = int_to.unwrap();
+                Ok(self.int_to_int(value, from_width, from_signed, to_width, to_signed))
+            }
+            _ => Err(AnalysisError::Unsupported(format!(
+                "Unsupported cast from {:?} to {:?}",
+                from, to
+            ))),
+        }
+    }
+
+    /// `as` between integers: truncate to the target width, then reinterpret the sign.
+    pub fn int_to_int(
+        &self,
+        value: &str,
+        from_width: u64,
+        from_signed: bool,
+        to_width: u64,
+        to_signed: bool,
+    ) -> String {
+        match self.encoding.int {
+            IntEncoding::Math => {
+                let fits = if from_signed == to_signed {
+                    to_width >= from_width
+                } else {
+                    !from_signed && to_width > from_width
+                };
+                if fits {
+                    return value.to_string();
+                }
+                let modulus = Analyzer::pow2(to_width);
+                if to_signed {
+                    format!(
+                        "(let ((m (mod {} {}))) (ite (>= m {}) (- m {}) m))",
+                        value,
+                        modulus,
+                        Analyzer::pow2(to_width - 1),
+                        modulus
+                    )
+                } else {
+                    format!("(mod {} {})", value, modulus)
+                }
+            }
+            IntEncoding::BitVector => match to_width.cmp(&from_width) {
+                Ordering::Less => format!("((_ extract {} 0) {})", to_width - 1, value),
+                Ordering::Greater => {
+                    let extend = if from_signed {
+                        "sign_extend"
+                    } else {
+                        "zero_extend"
+                    };
+                    format!("((_ {} {}) {})", extend, to_width - from_width, value)
+                }
+                Ordering::Equal => value.to_string(),
+            },
+        }
+    }
+
+    /// `as` from integer to float rounds to nearest, ties to even.
+    fn int_to_float(&self, value: &str, width: u64, signed: bool, float_ty: FloatTy) -> String {
+        let (eb, sb) = Encoding::float_bits(float_ty);
+        match (self.encoding.int, self.encoding.float) {
+            (IntEncoding::Math, FloatEncoding::Real) => format!("(to_real {})", value),
+            (IntEncoding::Math, FloatEncoding::Ieee) => {
+                format!("((_ to_fp {} {}) RNE (to_real {}))", eb, sb, value)
+            }
+            (IntEncoding::BitVector, FloatEncoding::Real) => {
+                format!("(to_real {})", Analyzer::bv_to_int(value, width, signed))
+            }
+            (IntEncoding::BitVector, FloatEncoding::Ieee) if signed => {
+                format!("((_ to_fp {} {}) RNE {})", eb, sb, value)
+            }
+            (IntEncoding::BitVector, FloatEncoding::Ieee) => {
+                format!("((_ to_fp_unsigned {} {}) RNE {})", eb, sb, value)
+            }
+        }
+    }
+
+    /// `as` from float to integer truncates toward zero, saturates at the bounds of the
+    /// target type and maps NaN to 0.
+    fn float_to_int(&self, value: &str, float_ty: FloatTy, width: u64, signed: bool) -> String {
+        let (min, max) = Analyzer::int_bounds(width, signed);
+        let saturate = |t: String| {
+            format!(
+                "(let ((t {})) (ite (< t {}) {} (ite (> t {}) {} t)))",
+                t, min, min, max, max
+            )
+        };
+        let int_value = match self.encoding.float {
+            FloatEncoding::Real => saturate(format!(
+                "(let ((r {})) (ite (>= r 0.0) (to_int r) (- (to_int (- r)))))",
+                value
+            )),
+            FloatEncoding::Ieee => {
+                let (eb, sb) = Encoding::float_bits(float_ty);
+                let zero = format!("(_ +zero {} {})", eb, sb);
+                format!(
+                    "(let ((f {})) (ite (fp.isNaN f) 0 (ite (fp.isInfinite f) (ite (fp.lt f {}) {} {}) {})))",
+                    value,
+                    zero,
+                    min,
+                    max,
+                    saturate("(to_int (fp.to_real (fp.roundToIntegral RTZ f)))".to_string())
+                )
+            }
+        };
+        match self.encoding.int {
+            IntEncoding::Math => int_value,
+            IntEncoding::BitVector => format!("((_ int2bv {}) {})", width, int_value),
+        }
+    }
+
+    /// In the mathematical encoding, constrains a fresh integer to the range of its type.
+    pub fn int_range_to_const(&self, name: &str, ty: Ty<'tcx>) -> Option<String> {
+        if self.encoding.int != IntEncoding::Math {
+            return None;
+        }
+        let (width, signed) = Encoding::int_width(ty)?;
+        let (min, max) = Analyzer::int_bounds(width, signed);
+        Some(format!("(and (<= {} {}) (<= {} {}))", min, name, name, max))
+    }
+
+    fn int_bounds(width: u64, signed: bool) -> (String, String) {
+        if signed {
+            (
+                format!("(- {})", Analyzer::pow2(width - 1)),
+                format!("{}", (1u128 << (width - 1)) - 1),
+            )
+        } else {
+            ("0".to_string(), format!("{}", u128::MAX >> (128 - width)))
+        }
+    }
+
+    fn bv_to_int(value: &str, width: u64, signed: bool) -> String {
+        if signed {
+            format!(
+                "(let ((b {})) (ite (bvslt b (_ bv0 {})) (- (bv2int b) {}) (bv2int b)))",
+                value,
+                width,
+                Analyzer::pow2(width)
+            )
+        } else {
+            format!("(bv2int {})", value)
+        }
+    }
+
+    fn pow2(exp: u64) -> String {
+        if exp < 128 {
+            format!("{}", 1u128 << exp)
+        } else {
+            "340282366920938463463374607431768211456".to_string()
+        }
+    }
+}
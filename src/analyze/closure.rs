@@ -0,0 +1,236 @@
+use rustc_middle::hir::place::PlaceBase;
+use rustc_middle::ty::{BorrowKind, EarlyBinder, Ty, TyKind, UpvarCapture};
+use rustc_span::def_id::DefId;
+use rustc_target::abi::{FieldIdx, VariantIdx};
+
+use crate::analyze::*;
+
+/// A closure value is a datatype holding the variables it captures by value. Variables
+/// captured by reference (and moved `&mut` references) are not copied: calls are inlined,
+/// so the closure body reads and writes the variables of the enclosing body directly.
+impl<'tcx> Env<'tcx> {
+    /// The variables stored in a closure value, in capture order, with their types.
+    pub fn closure_state(&self, closure_ty: Ty<'tcx>) -> Vec<(LocalVarId, Ty<'tcx>)> {
+        let TyKind::Closure(def_id, args) = closure_ty.kind() else {
+            return Vec::new();
+        };
+        let parent_args = args.as_closure().parent_args();
+        let mut state: Vec<(LocalVarId, Ty<'tcx>)> = Vec::new();
+        for captured in self.tcx.closure_captures(def_id.expect_local()) {
+            let PlaceBase::Upvar(upvar_id) = captured.place.base else {
+                continue;
+            };
+            let var = LocalVarId(upvar_id.var_path.hir_id);
+            let ty = self.tcx.erase_regions(
+                EarlyBinder::bind(captured.place.base_ty).instantiate(self.tcx, parent_args),
+            );
+            if captured.info.capture_kind == UpvarCapture::ByValue
+                && !Analyzer::is_mut_ref(ty)
+                && !state.iter().any(|(v, _)| *v == var)
+            {
+                state.push((var, ty));
+            }
+        }
+        state
+    }
+}
+
+impl<'tcx> Analyzer<'tcx> {
+    pub fn closure_to_const(
+        &self,
+        closure_ty: Ty<'tcx>,
+        upvars: &[Rc<RExpr<'tcx>>],
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let datatype = env.ty_to_sort(closure_ty)?;
+        let state = env.closure_state(closure_ty);
+        if state.is_empty() {
+            return Ok(Env::constructor_name(&datatype));
+        }
+        let mut values = Vec::new();
+        for (var, _) in state {
+            let root = upvars
+                .iter()
+                .map(Analyzer::capture_root)
+                .find(|root| Analyzer::captured_var(root) == Some(var))
+                .ok_or(AnalysisError::Unsupported(format!(
+                    "Captured variable {:?} not found",
+                    var
+                )))?;
+            values.push(self.expr_to_const(root, env)?);
+        }
+        Ok(format!(
+            "({} {})",
+            Env::constructor_name(&datatype),
+            values.join(" ")
+        ))
+    }
+
+    /// The variable a capture expression starts from, e.g. `p` for `&mut p.x`.
+    fn capture_root(expr: &Rc<RExpr<'tcx>>) -> Rc<RExpr<'tcx>> {
+        use RExprKind::*;
+        match &expr.kind {
+            Borrow { arg, .. } | Deref { arg } | Field { lhs: arg, .. } => {
+                Analyzer::capture_root(arg)
+            }
+            _ => expr.clone(),
+        }
+    }
+
+    fn captured_var(expr: &RExpr<'tcx>) -> Option<LocalVarId> {
+        match &expr.kind {
+            RExprKind::VarRef { id } => Some(*id),
+            RExprKind::UpvarRef { var_hir_id, .. } => Some(*var_hir_id),
+            _ => None,
+        }
+    }
+
+    /// The place of a captured variable inside the body of the closure being called, or
+    /// `None` if it is the variable of the enclosing body.
+    pub fn upvar_place(
+        &self,
+        closure_def_id: DefId,
+        var: LocalVarId,
+        env: &Env<'tcx>,
+    ) -> Result<Option<Place<'tcx>>, AnalysisError> {
+        let Some((self_place, closure_ty)) = env.closures.get(&closure_def_id).cloned() else {
+            return Ok(None);
+        };
+        match env.closure_state(closure_ty).iter().position(|(v, _)| *v == var) {
+            Some(idx) => Ok(Some(Place::Field {
+                base: Box::new(self_place.ok_or(AnalysisError::Unsupported(
+                    "Closures with by-value captures must be called through a variable"
+                        .to_string(),
+                ))?),
+                ty: closure_ty,
+                idx: FieldIdx::from_usize(idx),
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Havocs what a closure may write when it is called by code that is not analyzed: the
+    /// variables it captures by mutable reference and the targets of captured `&mut`s.
+    pub fn havoc_closure_captures(
+        &self,
+        closure_ty: Ty<'tcx>,
+        expr: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<(), AnalysisError> {
+        let TyKind::Closure(def_id, args) = closure_ty.kind() else {
+            return Ok(());
+        };
+        let parent_args = args.as_closure().parent_args();
+        for captured in self.tcx.closure_captures(def_id.expect_local()) {
+            let PlaceBase::Upvar(upvar_id) = captured.place.base else {
+                continue;
+            };
+            let var = LocalVarId(upvar_id.var_path.hir_id);
+            let ty = self.tcx.erase_regions(
+                EarlyBinder::bind(captured.place.base_ty).instantiate(self.tcx, parent_args),
+            );
+            let (place, ty) = match (captured.info.capture_kind, ty.kind()) {
+                (_, TyKind::Ref(_, inner_ty, _)) if Analyzer::is_mut_ref(ty) => {
+                    match env.ref_map.get(&var) {
+                        Some(place) => (place.clone(), *inner_ty),
+                        None => continue,
+                    }
+                }
+                (UpvarCapture::ByRef(BorrowKind::MutBorrow | BorrowKind::UniqueImmBorrow), _) => {
+                    (self.captured_var_place(var, env)?, ty)
+                }
+                _ => continue,
+            };
+            let name = env.fresh_name("havoc".to_string(), expr.span);
+            env.add_random_var(ty, name.clone());
+            if let Some(range) = self.int_range_to_const(&name, ty) {
+                env.add_smt_command(range, expr.clone());
+            }
+            env.write_place(&place, name)?;
+        }
+        Ok(())
+    }
+
+    /// The place of a variable of the enclosing body, which is a field of a closure value
+    /// when the body is itself an inlined closure capturing it by value.
+    fn captured_var_place(
+        &self,
+        var: LocalVarId,
+        env: &Env<'tcx>,
+    ) -> Result<Place<'tcx>, AnalysisError> {
+        let enclosing: Vec<DefId> = env.closures.keys().copied().collect();
+        for closure_def_id in enclosing {
+            if let Some(place) = self.upvar_place(closure_def_id, var, env)? {
+                return Ok(place);
+            }
+        }
+        Ok(Place::Var(var))
+    }
+
+    /// Inlines a call of a closure body. `args` are the closure itself and the tuple of
+    /// arguments of the "rust-call" ABI.
+    pub fn closure_call_to_const(
+        &self,
+        closure_def_id: DefId,
+        rthir: Rc<RThir<'tcx>>,
+        args: &[Rc<RExpr<'tcx>>],
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let closure = args[0].clone();
+        let mut call_args = vec![closure.clone()];
+        call_args.extend(Analyzer::untuple_args(args[1].clone())?);
+        let self_place = self.closure_self_place(closure.clone(), env);
+        let outer = env
+            .closures
+            .insert(closure_def_id, (self_place, closure.ty.peel_refs()));
+        let result = self.local_fn_to_const(rthir, call_args.into_boxed_slice(), env);
+        match outer {
+            Some(outer) => env.closures.insert(closure_def_id, outer),
+            None => env.closures.remove(&closure_def_id),
+        };
+        result
+    }
+
+    /// Splits the argument tuple of a call through the `Fn*` traits.
+    pub fn untuple_args(
+        tupled: Rc<RExpr<'tcx>>,
+    ) -> Result<Vec<Rc<RExpr<'tcx>>>, AnalysisError> {
+        match (&tupled.kind, tupled.ty.kind()) {
+            (RExprKind::Tuple { fields }, _) => Ok(fields.to_vec()),
+            (_, TyKind::Tuple(tys)) => Ok(tys
+                .iter()
+                .enumerate()
+                .map(|(idx, ty)| {
+                    Rc::new(RExpr::new(
+                        RExprKind::Field {
+                            lhs: tupled.clone(),
+                            variant_index: VariantIdx::from_u32(0),
+                            name: FieldIdx::from_usize(idx),
+                        },
+                        ty,
+                        tupled.span,
+                    ))
+                })
+                .collect()),
+            _ => Err(AnalysisError::Unsupported(
+                "Closure arguments must be a tuple".to_string(),
+            )),
+        }
+    }
+
+    fn closure_self_place(
+        &self,
+        expr: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Option<Place<'tcx>> {
+        match &expr.kind {
+            RExprKind::Borrow { arg, .. } => self.closure_self_place(arg.clone(), env),
+            // Shared references are encoded as the value they point to.
+            RExprKind::Deref { arg } if !Analyzer::is_mut_ref(arg.ty) => {
+                self.closure_self_place(arg.clone(), env)
+            }
+            _ if Analyzer::is_mut_ref(expr.ty) => self.ref_target(expr, env).ok(),
+            _ => self.expr_to_place(expr, env).ok(),
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use rustc_ast::ast::LitKind;
+use rustc_middle::ty::{AssocKind, TyKind};
+use rustc_span::def_id::DefId;
+
+use crate::analyze::*;
+use crate::thir::rthir::RThir;
+
+/// Trait method contracts are written as the default body of the trait method:
+///
+/// ```ignore
+/// fn area(&self) -> i32 {
+///     Vrequires(...);
+///     Vensures(Vresult::<i32>() >= 0);
+///     Vresult()
+/// }
+/// ```
+///
+/// Every impl is checked against the contract once, and calls whose impl is not known
+/// statically (`dyn Trait`) are replaced by the contract.
+impl<'tcx> Analyzer<'tcx> {
+    /// Arguments of the `verify_modules::<clause>` calls among the statements of `rthir`.
+    pub fn contract_clauses(&self, rthir: &RThir<'tcx>, clause: &str) -> Vec<Rc<RExpr<'tcx>>> {
+        let mut clauses = Vec::new();
+        if let Some(body) = &rthir.body {
+            if let RExprKind::Block { stmts, .. } = &body.kind {
+                for stmt in stmts.iter() {
+                    if let Some((name, arg)) = self.clause(stmt) {
+                        if name == clause {
+                            clauses.push(arg);
+                        }
+                    }
+                }
+            }
+        }
+        clauses
+    }
+
+    /// The name and argument of a `verify_modules::<clause>` call statement.
+    fn clause(&self, stmt: &RExpr<'tcx>) -> Option<(String, Rc<RExpr<'tcx>>)> {
+        let RExprKind::Call { ty, args, .. } = &stmt.kind else {
+            return None;
+        };
+        let TyKind::FnDef(def_id, _) = ty.kind() else {
+            return None;
+        };
+        let fn_info = self.get_fn_info(def_id);
+        let is_clause = fn_info[0] == "verify_modules"
+            && matches!(
+                fn_info[1].as_str(),
+                "Vrequires" | "Vensures" | "Vextern_spec"
+            );
+        is_clause.then(|| (fn_info[1].clone(), args[0].clone()))
+    }
+
+    /// The function without the clauses of its contract, i.e. its implementation.
+    fn without_clauses(&self, rthir: &RThir<'tcx>) -> RThir<'tcx> {
+        let body = rthir.body.as_ref().map(|body| match &body.kind {
+            RExprKind::Block { stmts, expr } => Rc::new(RExpr::new(
+                RExprKind::Block {
+                    stmts: stmts
+                        .iter()
+                        .filter(|stmt| self.clause(stmt).is_none())
+                        .cloned()
+                        .collect(),
+                    expr: expr.clone(),
+                },
+                body.ty,
+                body.span,
+            )),
+            _ => body.clone(),
+        });
+        RThir {
+            params: rthir.params.clone(),
+            body,
+        }
+    }
+
+    pub fn is_contract(&self, rthir: &RThir<'tcx>) -> bool {
+        !self.contract_clauses(rthir, "Vrequires").is_empty()
+            || !self.contract_clauses(rthir, "Vensures").is_empty()
+    }
+
+    /// The local spec standing for a function of another crate: a function whose body
+    /// starts with `Vextern_spec("path::of::the::function")` and whose contract is used in
+    /// place of the missing body.
+    pub fn find_extern_spec(&self, def_id: &DefId) -> Option<Rc<RThir<'tcx>>> {
+        let fn_info = self.get_fn_info(def_id);
+        self.fn_map.values().find_map(|rthir| {
+            self.contract_clauses(rthir, "Vextern_spec")
+                .iter()
+                .any(|path| Analyzer::extern_spec_path(path).as_ref() == Some(&fn_info))
+                .then(|| rthir.clone())
+        })
+    }
+
+    /// The path given to `Vextern_spec`, split into its segments.
+    fn extern_spec_path(expr: &RExpr<'tcx>) -> Option<Vec<String>> {
+        match &expr.kind {
+            RExprKind::Borrow { arg, .. } | RExprKind::Deref { arg } => {
+                Analyzer::extern_spec_path(arg)
+            }
+            RExprKind::Literal { lit, .. } => match lit.node {
+                LitKind::Str(path, _) => Some(Analyzer::split_def_path(path.as_str())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Replaces a call of `def_id` by its contract: the precondition is verified, the result
+    /// and the targets of `&mut` arguments are havocked, and the postcondition is assumed
+    /// unless the contract could not be checked.
+    pub fn contract_to_const(
+        &self,
+        def_id: DefId,
+        rthir: Rc<RThir<'tcx>>,
+        args: Box<[Rc<RExpr<'tcx>>]>,
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        self.analyze_params(&rthir.params, args, env)?;
+        for clause in self.contract_clauses(&rthir, "Vrequires") {
+            let constraint = self.expr_to_const(clause.clone(), env)?;
+            env.verify_z3(constraint, clause.span)?;
+        }
+        for param in rthir.params.iter() {
+            if let Some(pat) = &param.pat {
+                if let RExprKind::Pat {
+                    kind: RPatKind::Binding { ty, var, .. },
+                } = &pat.kind
+                {
+                    if let TyKind::Ref(_, inner_ty, _) = ty.kind() {
+                        if Analyzer::is_mut_ref(*ty) || self.is_cell_ref(*ty) {
+                            let name = env.fresh_name("havoc".to_string(), pat.span);
+                            env.add_random_var(*inner_ty, name.clone());
+                            let place = self.ref_target_of_var(*var, env)?;
+                            env.write_place(&place, name)?;
+                        }
+                    }
+                }
+            }
+        }
+        // The contract of a local function does not tell how it changes the global state.
+        if self.contract_clauses(&rthir, "Vextern_spec").is_empty() {
+            self.havoc_global_state(call.clone(), env);
+        }
+        let result = self.havoc_result(call.clone(), env);
+        if self.unchecked_contracts.borrow().contains(&def_id) {
+            println!(
+                "Warning: the postcondition of {} is not assumed at {:?} as it was not checked",
+                self.tcx.def_path_str(def_id),
+                call.span
+            );
+            return Ok(result);
+        }
+        let outer_result = env.result.replace(result.clone());
+        for clause in self.contract_clauses(&rthir, "Vensures") {
+            let constraint = self.expr_to_const(clause.clone(), env)?;
+            env.add_smt_command(constraint, clause);
+        }
+        env.result = outer_result;
+        Ok(result)
+    }
+
+    /// A dynamic call without a contract: nothing is known about the result.
+    pub fn havoc_call_to_const(
+        &self,
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        println!(
+            "Warning: call at {:?} cannot be resolved statically and has no contract; its result is unconstrained",
+            call.span
+        );
+        self.havoc_global_state(call.clone(), env);
+        Ok(self.havoc_result(call, env))
+    }
+
+    pub fn havoc_result(&self, call: Rc<RExpr<'tcx>>, env: &mut Env<'tcx>) -> String {
+        let name = env.fresh_name("result".to_string(), call.span);
+        env.add_random_var(call.ty, name.clone());
+        if let Some(range) = self.int_range_to_const(&name, call.ty) {
+            env.add_smt_command(range, call.clone());
+        }
+        name
+    }
+
+    fn ref_target_of_var(
+        &self,
+        var: LocalVarId,
+        env: &Env<'tcx>,
+    ) -> Result<Place<'tcx>, AnalysisError> {
+        env.ref_map
+            .get(&var)
+            .cloned()
+            .ok_or(AnalysisError::Unsupported(format!(
+                "Reference {:?} is not bound to a place",
+                var
+            )))
+    }
+
+    /// Checks every method of a local, non-generic trait impl against the contract of the
+    /// trait method it implements.
+    pub fn check_trait_contracts(&self) -> Result<(), AnalysisError> {
+        for impls in self.tcx.all_local_trait_impls(()).values() {
+            for impl_id in impls {
+                let Some(trait_ref) = self.tcx.impl_trait_ref(*impl_id) else {
+                    continue;
+                };
+                let trait_args = trait_ref.instantiate_identity().args;
+                for item in self.tcx.associated_items(*impl_id).in_definition_order() {
+                    let Some(trait_item) = item.trait_item_def_id else {
+                        continue;
+                    };
+                    if item.kind != AssocKind::Fn || !self.tcx.defaultness(trait_item).has_value()
+                    {
+                        continue;
+                    }
+                    let Some(contract) = self.get_local_instance(&trait_item, trait_args)? else {
+                        continue;
+                    };
+                    if !self.is_contract(&contract) {
+                        continue;
+                    }
+                    if self.tcx.generics_of(item.def_id).count() > 0 {
+                        println!(
+                            "Warning: contract of generic impl method {} is not checked",
+                            self.tcx.def_path_str(item.def_id)
+                        );
+                        self.unchecked_contracts.borrow_mut().insert(trait_item);
+                        continue;
+                    }
+                    let rthir = self.get_fn(item.def_id.expect_local())?;
+                    println!(
+                        "Checking {} against the contract of {}",
+                        self.tcx.def_path_str(item.def_id),
+                        self.tcx.def_path_str(trait_item)
+                    );
+                    match self.check_impl_contract(rthir, contract) {
+                        Err(AnalysisError::Unsupported(reason)) => {
+                            println!(
+                                "Warning: contract of {} is not checked ({})",
+                                self.tcx.def_path_str(item.def_id),
+                                reason
+                            );
+                            self.unchecked_contracts.borrow_mut().insert(trait_item);
+                        }
+                        result => result?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks every other local function with a contract against it once, so that the
+    /// contract can stand for the function at its calls. Trait methods are checked per
+    /// impl, and extern specs have no implementation.
+    pub fn check_fn_contracts(&self) -> Result<(), AnalysisError> {
+        let mut fns: Vec<(DefId, Rc<RThir<'tcx>>)> = self
+            .fn_map
+            .iter()
+            .map(|(id, rthir)| (id.to_def_id(), rthir.clone()))
+            .filter(|(def_id, rthir)| {
+                self.is_contract(rthir)
+                    && self.tcx.trait_of_item(*def_id).is_none()
+                    && !self.tcx.is_closure_like(*def_id)
+                    && self.contract_clauses(rthir, "Vextern_spec").is_empty()
+            })
+            .collect();
+        fns.sort_by_key(|(def_id, _)| def_id.index);
+        for (def_id, contract) in fns {
+            if self.tcx.generics_of(def_id).count() > 0 {
+                println!(
+                    "Warning: contract of generic function {} is not checked",
+                    self.tcx.def_path_str(def_id)
+                );
+                self.unchecked_contracts.borrow_mut().insert(def_id);
+                continue;
+            }
+            println!(
+                "Checking {} against its contract",
+                self.tcx.def_path_str(def_id)
+            );
+            let rthir = Rc::new(self.without_clauses(&contract));
+            match self.check_impl_contract(rthir, contract) {
+                Err(AnalysisError::Unsupported(reason)) => {
+                    println!(
+                        "Warning: contract of {} is not checked ({})",
+                        self.tcx.def_path_str(def_id),
+                        reason
+                    );
+                    self.unchecked_contracts.borrow_mut().insert(def_id);
+                }
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+
+    fn check_impl_contract(
+        &self,
+        rthir: Rc<RThir<'tcx>>,
+        contract: Rc<RThir<'tcx>>,
+    ) -> Result<(), AnalysisError> {
+        let mut env = Env::new(self.tcx, self.encoding);
+        if let Some(body) = &rthir.body {
+            self.init_global_state(false, body.clone(), &mut env)?;
+        }
+        for (param, contract_param) in rthir.params.iter().zip(contract.params.iter()) {
+            let (Some(pat), Some(contract_pat)) = (&param.pat, &contract_param.pat) else {
+                continue;
+            };
+            let (
+                RExprKind::Pat {
+                    kind: RPatKind::Binding { ty, var, .. },
+                },
+                RExprKind::Pat {
+                    kind:
+                        RPatKind::Binding {
+                            var: contract_var, ..
+                        },
+                },
+            ) = (&pat.kind, &contract_pat.kind)
+            else {
+                return Err(AnalysisError::Unsupported(
+                    "Unsupported pattern in parameter".to_string(),
+                ));
+            };
+            let name = format!("arg_{}", Analyzer::get_name_from_span(pat.span));
+            // A `&mut` parameter points to an arbitrary value, kept in the parameter itself.
+            if let TyKind::Ref(_, inner_ty, _) = ty.kind() {
+                if Analyzer::is_mut_ref(*ty) || self.is_cell_ref(*ty) {
+                    env.add_random_var(*inner_ty, name.clone());
+                    if let Some(range) = self.int_range_to_const(&name, *inner_ty) {
+                        env.add_smt_command(range, pat.clone());
+                    }
+                    env.add_param(name.clone(), *inner_ty, *var, pat.clone());
+                    env.assign_value(*var, name, pat.clone());
+                    env.ref_map.insert(*var, Place::Var(*var));
+                    env.ref_map.insert(*contract_var, Place::Var(*var));
+                    continue;
+                }
+            }
+            env.add_random_var(*ty, name.clone());
+            if let Some(range) = self.int_range_to_const(&name, *ty) {
+                env.add_smt_command(range, pat.clone());
+            }
+            for var in [var, contract_var] {
+                env.add_param(name.clone(), *ty, *var, pat.clone());
+                env.assign_value(*var, name.clone(), pat.clone());
+            }
+        }
+        for clause in self.contract_clauses(&contract, "Vrequires") {
+            let constraint = self.expr_to_const(clause.clone(), &mut env)?;
+            env.add_smt_command(constraint, clause);
+        }
+        env.result = Some(self.fn_body_to_const(&rthir, &mut env)?);
+        for clause in self.contract_clauses(&contract, "Vensures") {
+            let constraint = self.expr_to_const(clause.clone(), &mut env)?;
+            env.verify_z3(constraint, clause.span)?;
+        }
+        Ok(())
+    }
+}
@@ -3,8 +3,11 @@ use crate::thir::rthir::RThir;
 
 impl<'tcx> Analyzer<'tcx> {
     pub fn analyze_enter(&self, rthir: Rc<RThir<'tcx>>) -> Result<(), AnalysisError> {
+        self.check_trait_contracts()?;
+        self.check_fn_contracts()?;
         if let Some(body) = &rthir.body {
-            let mut main_env = Env::new();
+            let mut main_env = Env::new(self.tcx, self.encoding);
+            self.init_global_state(true, body.clone(), &mut main_env)?;
             self.analyze_body((*body).clone(), &mut main_env)?
         }
         Ok(())
@@ -15,6 +18,9 @@ impl<'tcx> Analyzer<'tcx> {
         body: Rc<RExpr<'tcx>>,
         env: &mut Env<'tcx>,
     ) -> Result<(), AnalysisError> {
+        if let RExprKind::NeverToAny { source } = &body.kind {
+            return self.analyze_body(source.clone(), env);
+        }
         if let RExprKind::Block { stmts, expr } = &body.kind {
             let mut stmts = stmts.clone().into_iter().peekable();
             while let Some(stmt) = stmts.next() {
@@ -24,9 +30,13 @@ impl<'tcx> Analyzer<'tcx> {
                         self.analyze_expr(expr.clone(), env)?; //loop is currently not supported
                     }
                     AnalysisType::Break => break,
-                    AnalysisType::Return(_) => break,
+                    AnalysisType::Return => break,
                     AnalysisType::Other => (),
                 }
+                // Both branches of a statement may have returned.
+                if env.returned.is_some() {
+                    return Ok(());
+                }
             }
             if let Some(expr) = expr {
                 self.analyze_expr(expr.clone(), env)?;
@@ -45,8 +55,9 @@ impl<'tcx> Analyzer<'tcx> {
     ) -> Result<AnalysisType<'tcx>, AnalysisError> {
         use RExprKind::*;
         let mut res = AnalysisType::Other;
+        let diverging = Analyzer::diverges(&expr).then(|| expr.clone());
         match expr.kind.clone() {
-            Literal { .. } => {
+            Literal { .. } | NonHirLiteral { .. } | ZstLiteral { .. } => {
                 self.analyze_literal(expr, env)?;
             }
             Binary { .. } => {
@@ -71,8 +82,12 @@ impl<'tcx> Analyzer<'tcx> {
             AssignOp { op, lhs, rhs } => {
                 self.analyze_assign_op(op, lhs, rhs, env)?;
             }
-            Loop { body } => {
-                self.analyze_body(body, env)?;
+            Loop { body } => match Analyzer::while_parts(&body) {
+                Some((cond, body)) => self.analyze_while(cond, body, expr, env)?,
+                None => self.analyze_body(body, env)?,
+            },
+            NeverToAny { source } => {
+                res = self.analyze_expr(source, env)?;
             }
             If {
                 cond,
@@ -81,20 +96,34 @@ impl<'tcx> Analyzer<'tcx> {
             } => {
                 self.analyze_if(cond, then, else_opt, env)?;
             }
-            Return { value } => match value {
-                Some(value) => {
-                    let constraint = self.expr_to_const(value.clone(), env)?;
-                    env.add_smt_command(constraint.clone(), value.clone());
-                    res = AnalysisType::Return(Some(constraint));
-                }
-                None => res = AnalysisType::Return(None),
-            },
+            Match { scrutinee, arms } => {
+                self.match_to_const(
+                    scrutinee,
+                    &arms,
+                    &|analyzer, body, env| {
+                        analyzer.analyze_expr(body, env)?;
+                        Ok("unit".to_string())
+                    },
+                    env,
+                )?;
+            }
+            Return { value } => {
+                let value = match value {
+                    Some(value) => self.expr_to_const(value, env)?,
+                    None => self.zst_to_const(self.tcx.types.unit, env)?,
+                };
+                env.return_value(value, expr.clone());
+                res = AnalysisType::Return;
+            }
             _ => {
                 return Err(AnalysisError::Unsupported(
                     format!("Unsupported expression {:?}", expr.kind).to_string(),
                 ))
             }
         }
+        if let Some(expr) = diverging {
+            env.diverge(expr);
+        }
         Ok(res)
     }
 }
@@ -104,7 +133,7 @@ pub enum AnalysisType<'tcx> {
     Invariant(Rc<RExpr<'tcx>>),
     Break,
     Other,
-    Return(Option<String>),
+    Return,
 }
 
 #[derive(Debug)]
@@ -0,0 +1,209 @@
+use rustc_middle::mir::BinOp;
+use rustc_middle::ty::{FloatTy, Ty, TyKind};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntEncoding {
+    /// Integers are unbounded mathematical integers.
+    #[default]
+    Math,
+    /// Integers are fixed-width bit-vectors with wrapping arithmetic.
+    BitVector,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FloatEncoding {
+    /// Floats are mathematical reals; rounding, NaN and infinities are ignored.
+    #[default]
+    Real,
+    /// Floats are IEEE-754 values of the SMT FloatingPoint theory (round-to-nearest-even).
+    Ieee,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Encoding {
+    pub int: IntEncoding,
+    pub float: FloatEncoding,
+}
+
+impl Encoding {
+    /// Bit width and signedness of integer-like types; `char` is treated as `u32`.
+    pub fn int_width(ty: Ty<'_>) -> Option<(u64, bool)> {
+        match ty.kind() {
+            TyKind::Int(int_ty) => Some((int_ty.bit_width().unwrap_or(64), true)),
+            TyKind::Uint(uint_ty) => Some((uint_ty.bit_width().unwrap_or(64), false)),
+            TyKind::Char => Some((32, false)),
+            _ => None,
+        }
+    }
+
+    pub fn int_sort(&self, width: u64) -> String {
+        match self.int {
+            IntEncoding::Math => "Int".to_string(),
+            IntEncoding::BitVector => format!("(_ BitVec {})", width),
+        }
+    }
+
+    pub fn int_to_const(&self, value: u128, neg: bool, width: u64) -> String {
+        match self.int {
+            IntEncoding::Math if neg => format!("(- {})", value),
+            IntEncoding::Math => format!("{}", value),
+            IntEncoding::BitVector => {
+                let value = if neg { value.wrapping_neg() } else { value };
+                let value = if width >= 128 {
+                    value
+                } else {
+                    value & ((1u128 << width) - 1)
+                };
+                format!("(_ bv{} {})", value, width)
+            }
+        }
+    }
+
+    pub fn bv_binop_to_const(
+        &self,
+        op: BinOp,
+        lhs: &str,
+        rhs: &str,
+        width: u64,
+        signed: bool,
+    ) -> Option<String> {
+        use BinOp::*;
+        let bv_op = match (op, signed) {
+            (Add, _) => "bvadd",
+            (Sub, _) => "bvsub",
+            (Mul, _) => "bvmul",
+            (Div, true) => "bvsdiv",
+            (Div, false) => "bvudiv",
+            (Rem, true) => "bvsrem",
+            (Rem, false) => "bvurem",
+            (BitAnd, _) => "bvand",
+            (BitOr, _) => "bvor",
+            (BitXor, _) => "bvxor",
+            (Eq, _) => "=",
+            (Ne, _) => "distinct",
+            (Lt, true) => "bvslt",
+            (Lt, false) => "bvult",
+            (Le, true) => "bvsle",
+            (Le, false) => "bvule",
+            (Gt, true) => "bvsgt",
+            (Gt, false) => "bvugt",
+            (Ge, true) => "bvsge",
+            (Ge, false) => "bvuge",
+            // Shift amounts are masked to the bit width, like `wrapping_shl`/`wrapping_shr`.
+            (Shl | Shr, _) => {
+                let amount = format!("(bvand {} (_ bv{} {}))", rhs, width - 1, width);
+                let shift_op = match (op, signed) {
+                    (Shl, _) => "bvshl",
+                    (_, true) => "bvashr",
+                    (_, false) => "bvlshr",
+                };
+                return Some(format!("({} {} {})", shift_op, lhs, amount));
+            }
+            _ => return None,
+        };
+        Some(format!("({} {} {})", bv_op, lhs, rhs))
+    }
+
+    pub fn int_unop_to_const(&self, neg: bool, arg: &str) -> Option<String> {
+        match (self.int, neg) {
+            (IntEncoding::Math, true) => Some(format!("(- {})", arg)),
+            (IntEncoding::Math, false) => None,
+            (IntEncoding::BitVector, true) => Some(format!("(bvneg {})", arg)),
+            (IntEncoding::BitVector, false) => Some(format!("(bvnot {})", arg)),
+        }
+    }
+
+    pub fn float_sort(&self, float_ty: FloatTy) -> String {
+        match self.float {
+            FloatEncoding::Real => "Real".to_string(),
+            FloatEncoding::Ieee => {
+                let (eb, sb) = Encoding::float_bits(float_ty);
+                format!("(_ FloatingPoint {} {})", eb, sb)
+            }
+        }
+    }
+
+    pub fn float_to_const(&self, value: f64, float_ty: FloatTy) -> String {
+        match self.float {
+            FloatEncoding::Real => Encoding::real_to_const(value),
+            FloatEncoding::Ieee => {
+                let (eb, sb) = Encoding::float_bits(float_ty);
+                let bits = match float_ty {
+                    FloatTy::F32 => format!("#x{:08x}", (value as f32).to_bits()),
+                    _ => format!("#x{:016x}", value.to_bits()),
+                };
+                format!("((_ to_fp {} {}) {})", eb, sb, bits)
+            }
+        }
+    }
+
+    pub fn ieee_binop_to_const(&self, op: BinOp, lhs: &str, rhs: &str) -> Option<String> {
+        use BinOp::*;
+        Some(match op {
+            Add => format!("(fp.add RNE {} {})", lhs, rhs),
+            Sub => format!("(fp.sub RNE {} {})", lhs, rhs),
+            Mul => format!("(fp.mul RNE {} {})", lhs, rhs),
+            Div => format!("(fp.div RNE {} {})", lhs, rhs),
+            // Rust's `%` truncates like C's fmod, while fp.rem rounds the quotient to
+            // nearest; shift the IEEE remainder by |rhs| when its sign disagrees with lhs.
+            Rem => format!(
+                "(let ((r (fp.rem {lhs} {rhs}))) (ite (and (not (fp.isZero r)) (not (= (fp.isNegative r) (fp.isNegative {lhs})))) (ite (fp.isNegative {lhs}) (fp.sub RNE r (fp.abs {rhs})) (fp.add RNE r (fp.abs {rhs}))) r))",
+                lhs = lhs,
+                rhs = rhs
+            ),
+            // IEEE comparisons are false whenever an operand is NaN, like Rust's PartialOrd.
+            Eq => format!("(fp.eq {} {})", lhs, rhs),
+            Ne => format!("(not (fp.eq {} {}))", lhs, rhs),
+            Lt => format!("(fp.lt {} {})", lhs, rhs),
+            Le => format!("(fp.leq {} {})", lhs, rhs),
+            Gt => format!("(fp.gt {} {})", lhs, rhs),
+            Ge => format!("(fp.geq {} {})", lhs, rhs),
+            _ => return None,
+        })
+    }
+
+    pub fn float_neg_to_const(&self, arg: &str) -> String {
+        match self.float {
+            FloatEncoding::Real => format!("(- {})", arg),
+            FloatEncoding::Ieee => format!("(fp.neg {})", arg),
+        }
+    }
+
+    pub fn float_bits(float_ty: FloatTy) -> (u32, u32) {
+        match float_ty {
+            FloatTy::F16 => (5, 11),
+            FloatTy::F32 => (8, 24),
+            FloatTy::F64 => (11, 53),
+            FloatTy::F128 => (15, 113),
+        }
+    }
+
+    pub fn real_to_const(value: f64) -> String {
+        let value_str = format!("{}", value.abs());
+        let value_str = if value_str.contains('.') {
+            value_str
+        } else {
+            format!("{}.0", value_str)
+        };
+        if value.is_sign_negative() && value != 0.0 {
+            format!("(- {})", value_str)
+        } else {
+            value_str
+        }
+    }
+
+    /// An SMT-LIB string literal: quotes are doubled and characters outside printable
+    /// ASCII are written as `\u{..}` escapes.
+    pub fn str_to_const(value: &str) -> String {
+        let mut literal = String::from("\"");
+        for c in value.chars() {
+            match c {
+                '"' => literal.push_str("\"\""),
+                ' '..='~' if c != '\\' => literal.push(c),
+                _ => literal.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            }
+        }
+        literal.push('"');
+        literal
+    }
+}
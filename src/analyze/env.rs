@@ -1,6 +1,8 @@
 use rustc_middle::thir::LocalVarId;
-use rustc_middle::ty::{Ty, TyKind};
-use rustc_span::Span;
+use rustc_middle::ty::{Mutability, Ty, TyCtxt, TyKind};
+use rustc_span::{def_id::DefId, Span};
+use rustc_target::abi::VariantIdx;
+use std::cell::{Cell, RefCell};
 use std::io::Write;
 use std::process::{Child, Command, Stdio};
 use std::rc::Rc;
@@ -8,43 +10,122 @@ use std::rc::Rc;
 use std::collections::HashMap;
 
 use crate::analyze::core::AnalysisError;
+use crate::analyze::encoding::Encoding;
+use crate::analyze::fn_ptr::FnPtrTarget;
 use crate::analyze::lir::Lir;
+use crate::analyze::place::Place;
 use crate::analyze::Analyzer;
 use crate::analyze::LirKind;
 use crate::analyze::RExpr;
 
+const UNIT_DECL: &str = "(declare-datatypes ((Unit 0)) (((unit))))";
+
+/// A return taken where `cond` holds. The path goes on under `(not cond)`; `env` is the
+/// state the function returned in.
+#[derive(Clone)]
+pub struct Return<'tcx> {
+    pub cond: String,
+    pub value: String,
+    pub expr: Rc<RExpr<'tcx>>,
+    pub env: Box<Env<'tcx>>,
+}
+
+#[derive(Clone)]
 pub struct Env<'tcx> {
     pub name: String,
     pub path: Vec<Lir<'tcx>>,
     pub vars: Vec<(Ty<'tcx>, String)>,
     pub env_map: HashMap<LocalVarId, Lir<'tcx>>,
+    pub ref_map: HashMap<LocalVarId, Place<'tcx>>,
+    /// The mutable global state: the type and value of every mutable or interior-mutable
+    /// static and of the slot of every `thread_local!` key.
+    pub statics: HashMap<DefId, (Ty<'tcx>, String)>,
+    /// The closures being called, with the place holding the closure value.
+    pub closures: HashMap<DefId, (Option<Place<'tcx>>, Ty<'tcx>)>,
+    /// The functions that may flow into function pointers on this path.
+    pub fn_ptrs: Vec<FnPtrTarget<'tcx>>,
+    pub datatypes: Rc<RefCell<Vec<(String, String)>>>,
+    /// The number of fresh constants declared so far, shared by all the environments of a run.
+    pub fresh: Rc<Cell<usize>>,
+    /// The value of `Vresult()` while a postcondition is evaluated.
+    pub result: Option<String>,
+    /// The diverging expression that ended the current path, if any.
+    pub diverged: Option<Rc<RExpr<'tcx>>>,
+    /// The value the current path returned from its function with, and the `return`.
+    pub returned: Option<(String, Rc<RExpr<'tcx>>)>,
+    /// The returns of the functions being called taken on some of the paths so far.
+    pub returns: Vec<Return<'tcx>>,
+    pub encoding: Encoding,
+    pub tcx: TyCtxt<'tcx>,
 }
 
 impl<'tcx> Env<'tcx> {
-    pub fn new() -> Self {
+    pub fn new(tcx: TyCtxt<'tcx>, encoding: Encoding) -> Self {
         Self {
             name: String::from("main"),
             path: Vec::new(),
             env_map: HashMap::new(),
+            ref_map: HashMap::new(),
+            statics: HashMap::new(),
+            closures: HashMap::new(),
+            fn_ptrs: Vec::new(),
+            datatypes: Rc::new(RefCell::new(Vec::new())),
+            fresh: Rc::new(Cell::new(0)),
             vars: Vec::new(),
+            result: None,
+            diverged: None,
+            returned: None,
+            returns: Vec::new(),
+            encoding,
+            tcx,
         }
     }
 
-    pub fn from(
-        name: String,
-        path: Vec<Lir<'tcx>>,
-        env_map: HashMap<LocalVarId, Lir<'tcx>>,
-        vars: Vec<(Ty<'tcx>, String)>,
-    ) -> Self {
-        Self {
-            name,
-            path,
-            env_map,
-            vars,
+    pub fn verify_z3(&self, assert: String, span: Span) -> Result<(), AnalysisError> {
+        let output_str = self.check_sat(format!("(not {})", assert.clone()), false)?;
+        if output_str.contains("unsat") {
+            println!("Verification succeeded :)");
+            // Unreachability goals hold exactly when the path is infeasible.
+            if assert != "false" && !self.is_feasible()? {
+                println!(
+                    "Warning: the assertion at {:?} holds vacuously: no execution reaches it",
+                    span
+                );
+            }
+            Ok(())
+        } else {
+            Err(AnalysisError::VerificationFailed)
         }
     }
 
-    pub fn verify_z3(&self, assert: String, span: Span) -> Result<(), AnalysisError> {
+    /// Whether some execution reaches the end of the current path.
+    pub fn is_feasible(&self) -> Result<bool, AnalysisError> {
+        let output_str = self.check_sat("true".to_string(), false)?;
+        Ok(output_str.split_whitespace().next() != Some("unsat"))
+    }
+
+    /// Whether some execution reaches the end of the current path with `cond` holding;
+    /// prints a witness if one does. The model is only asked for once the goal is known to
+    /// be satisfiable, as z3 reports an error otherwise.
+    pub fn cover_z3(&self, cond: String, span: Span) -> Result<(), AnalysisError> {
+        let output_str = self.check_sat(cond.clone(), false)?;
+        match output_str.split_whitespace().next() {
+            Some("sat") => println!(
+                "Cover at {:?} is reachable; witness:\n{}",
+                span,
+                self.check_sat(cond, true)?
+                    .trim_start()
+                    .trim_start_matches("sat")
+                    .trim()
+            ),
+            Some("unsat") => println!("Warning: cover at {:?} is unreachable", span),
+            _ => println!("Warning: cover at {:?} could not be decided", span),
+        }
+        Ok(())
+    }
+
+    /// Runs z3 on the current path with `goal` asserted and returns its output.
+    fn check_sat(&self, goal: String, get_model: bool) -> Result<String, AnalysisError> {
         let mut command = Command::new("z3")
             .arg("-in")
             .stdin(std::process::Stdio::piped())
@@ -54,8 +135,11 @@ impl<'tcx> Env<'tcx> {
         let mut smt_str = String::new();
 
         smt_str.push_str(&self.get_smt_commands()?);
-        smt_str.push_str(format!("\n(assert (not {}))\n", assert).as_str());
+        smt_str.push_str(format!("\n(assert {})\n", goal).as_str());
         smt_str.push_str("\n(check-sat)\n");
+        if get_model {
+            smt_str.push_str("(get-model)\n");
+        }
 
         let stdin = command.stdin.as_mut().expect("Failed to open stdin");
         stdin
@@ -68,12 +152,7 @@ impl<'tcx> Env<'tcx> {
 
         println!("SMT: \n {}", smt_str);
         println!("Output: \n {}", output_str);
-        if output_str.contains("unsat") {
-            println!("Verification succeeded :)");
-            Ok(())
-        } else {
-            Err(AnalysisError::VerificationFailed)
-        }
+        Ok(output_str)
     }
 
     pub fn len(&self) -> usize {
@@ -84,6 +163,53 @@ impl<'tcx> Env<'tcx> {
         self.path.push(Lir::new_assume(constraint, expr, None));
     }
 
+    /// Ends the current path after a diverging expression: the code after it is unreachable.
+    pub fn diverge(&mut self, expr: Rc<RExpr<'tcx>>) {
+        if self.diverged.is_none() {
+            self.add_smt_command("false".to_string(), expr.clone());
+            self.diverged = Some(expr);
+        }
+    }
+
+    /// Ends the current path at a `return` of its function.
+    pub fn return_value(&mut self, value: String, expr: Rc<RExpr<'tcx>>) {
+        if self.returned.is_none() {
+            self.returned = Some((value, expr));
+        }
+    }
+
+    /// Folds the returns taken since `depth` returns were pending back into the state, so
+    /// that it holds on every path again. Returns where and with which value they were
+    /// taken, and one of the `return`s.
+    pub fn fold_returns(
+        &mut self,
+        depth: usize,
+    ) -> Result<Option<(String, String, Rc<RExpr<'tcx>>)>, AnalysisError> {
+        let mut ret = self
+            .returned
+            .take()
+            .map(|(value, expr)| ("true".to_string(), value, expr));
+        while self.returns.len() > depth {
+            let Return {
+                cond,
+                value,
+                expr,
+                env,
+            } = self.returns.pop().unwrap();
+            let rest = std::mem::replace(self, *env);
+            self.merge_ite_env(&format!("(not {})", cond), rest, None)?;
+            ret = Some(match ret {
+                Some((rest_cond, rest_value, _)) => (
+                    format!("(or {} {})", cond, rest_cond),
+                    format!("(ite {} {} {})", cond, value, rest_value),
+                    expr,
+                ),
+                None => (cond, value, expr),
+            });
+        }
+        Ok(ret)
+    }
+
     pub fn get_smt_commands(&self) -> Result<String, AnalysisError> {
         let smt_var_str = self
             .vars
@@ -95,8 +221,16 @@ impl<'tcx> Env<'tcx> {
             .iter()
             .map(|smt_command| self.path_to_smt(smt_command).unwrap())
             .collect::<Vec<String>>();
+        let datatype_str = self
+            .datatypes
+            .borrow()
+            .iter()
+            .map(|(_, decl)| decl.clone())
+            .collect::<Vec<String>>();
         Ok(format!(
-            "{}\n{}",
+            "{}\n{}\n{}\n{}",
+            UNIT_DECL,
+            datatype_str.join("\n"),
             smt_var_str.join("\n"),
             smt_str.join("\n")
         ))
@@ -104,15 +238,223 @@ impl<'tcx> Env<'tcx> {
 
     pub fn var_to_smt(&self, var: &(Ty<'tcx>, String)) -> Result<String, AnalysisError> {
         let (ty, name) = var;
+        Ok(format!(
+            "(declare-const {} {})",
+            name,
+            self.ty_to_sort(*ty)?
+        ))
+    }
+
+    pub fn ty_to_sort(&self, ty: Ty<'tcx>) -> Result<String, AnalysisError> {
         match ty.kind() {
-            TyKind::Bool => Ok(format!("(declare-const {} Bool)", name)),
-            TyKind::Int(_) => Ok(format!("(declare-const {} Int)", name)),
-            TyKind::Float(_) => Ok(format!("(declare-const {} Real)", name)),
-            _ => Err(AnalysisError::Unsupported(
-                "Unsupported variable type".to_string(),
+            TyKind::Bool => Ok("Bool".to_string()),
+            TyKind::Int(_) | TyKind::Uint(_) | TyKind::Char => {
+                let (width, _) = Encoding::int_width(ty).expect("integer type has a width");
+                Ok(self.encoding.int_sort(width))
+            }
+            TyKind::Float(float_ty) => Ok(self.encoding.float_sort(*float_ty)),
+            TyKind::Str => Ok("String".to_string()),
+            TyKind::Tuple(fields) if fields.is_empty() => Ok("Unit".to_string()),
+            TyKind::Tuple(_) => self.declare_datatype(ty),
+            TyKind::Adt(adt_def, _) if adt_def.is_struct() && !adt_def.is_box() => {
+                self.declare_datatype(ty)
+            }
+            TyKind::Adt(adt_def, _) if adt_def.is_enum() && !adt_def.variants().is_empty() => {
+                self.declare_enum_datatype(ty)
+            }
+            // Uninhabited types have no values, e.g. the residual `Option<Infallible>` of `?`.
+            TyKind::Adt(adt_def, _) if adt_def.is_enum() => Ok("Unit".to_string()),
+            TyKind::Never => Ok("Unit".to_string()),
+            TyKind::Closure(..) => self.declare_datatype(ty),
+            TyKind::Array(elem_ty, _) => Ok(format!(
+                "(Array {} {})",
+                self.encoding.int_sort(64),
+                self.ty_to_sort(*elem_ty)?
             )),
+            // Trait objects are opaque; only the contracts of their methods are known.
+            TyKind::Dynamic(..) => Ok(self.declare_sort(ty)),
+            // Function pointers are compared against the functions reified on the path.
+            TyKind::FnPtr(..) => Ok(self.declare_sort(ty)),
+            // Function items are zero-sized.
+            TyKind::FnDef(..) => Ok("Unit".to_string()),
+            // Shared references are encoded as the value they point to.
+            TyKind::Ref(_, inner_ty, Mutability::Not) => self.ty_to_sort(*inner_ty),
+            _ => Err(AnalysisError::Unsupported(format!(
+                "Unsupported variable type {:?}",
+                ty
+            ))),
+        }
+    }
+
+    fn declare_sort(&self, ty: Ty<'tcx>) -> String {
+        let name = Env::datatype_name(ty);
+        let mut datatypes = self.datatypes.borrow_mut();
+        if !datatypes.iter().any(|(n, _)| *n == name) {
+            datatypes.push((name.clone(), format!("(declare-sort {} 0)", name)));
+        }
+        name
+    }
+
+    /// Declares a struct or tuple type as a single-constructor SMT datatype.
+    fn declare_datatype(&self, ty: Ty<'tcx>) -> Result<String, AnalysisError> {
+        let name = Env::datatype_name(ty);
+        if self.datatypes.borrow().iter().any(|(n, _)| *n == name) {
+            return Ok(name);
+        }
+        let mut selectors = Vec::new();
+        for (idx, field_ty) in self.field_tys(ty).into_iter().enumerate() {
+            selectors.push(format!(
+                "({} {})",
+                Env::selector_name(&name, idx),
+                self.ty_to_sort(field_ty)?
+            ));
+        }
+        let decl = format!(
+            "(declare-datatypes (({} 0)) ((({} {}))))",
+            name,
+            Env::constructor_name(&name),
+            selectors.join(" ")
+        );
+        self.datatypes.borrow_mut().push((name.clone(), decl));
+        Ok(name)
+    }
+
+    /// Declares an enum as an SMT datatype with one constructor per variant.
+    fn declare_enum_datatype(&self, ty: Ty<'tcx>) -> Result<String, AnalysisError> {
+        let name = Env::datatype_name(ty);
+        if self.datatypes.borrow().iter().any(|(n, _)| *n == name) {
+            return Ok(name);
+        }
+        let TyKind::Adt(adt_def, args) = ty.kind() else {
+            unreachable!()
+        };
+        let mut constructors = Vec::new();
+        for (variant_idx, variant) in adt_def.variants().iter_enumerated() {
+            let mut constructor = vec![self.variant_constructor_name(&name, ty, variant_idx)];
+            for (idx, field) in variant.fields.iter().enumerate() {
+                constructor.push(format!(
+                    "({} {})",
+                    self.variant_selector_name(&name, ty, variant_idx, idx),
+                    self.ty_to_sort(field.ty(self.tcx, args))?
+                ));
+            }
+            constructors.push(format!("({})", constructor.join(" ")));
+        }
+        let decl = format!(
+            "(declare-datatypes (({} 0)) (({})))",
+            name,
+            constructors.join(" ")
+        );
+        self.datatypes.borrow_mut().push((name.clone(), decl));
+        Ok(name)
+    }
+
+    pub fn field_tys(&self, ty: Ty<'tcx>) -> Vec<Ty<'tcx>> {
+        match ty.kind() {
+            TyKind::Tuple(tys) => tys.iter().collect(),
+            TyKind::Adt(adt_def, args) if adt_def.is_struct() => adt_def
+                .non_enum_variant()
+                .fields
+                .iter()
+                .map(|field| field.ty(self.tcx, args))
+                .collect(),
+            TyKind::Closure(..) => self
+                .closure_state(ty)
+                .into_iter()
+                .map(|(_, ty)| ty)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn datatype_name(ty: Ty<'tcx>) -> String {
+        let name = format!("{}", ty).replace(|c: char| !c.is_alphanumeric(), "_");
+        match ty.kind() {
+            TyKind::Tuple(_) => format!("Tuple{}", name),
+            _ => name,
+        }
+    }
+
+    pub fn constructor_name(datatype: &str) -> String {
+        format!("mk_{}", datatype)
+    }
+
+    pub fn selector_name(datatype: &str, idx: usize) -> String {
+        format!("{}_{}", datatype, idx)
+    }
+
+    /// The constructor of a variant; structs and tuples have a single one.
+    pub fn variant_constructor_name(
+        &self,
+        datatype: &str,
+        ty: Ty<'tcx>,
+        variant: VariantIdx,
+    ) -> String {
+        match ty.kind() {
+            TyKind::Adt(adt_def, _) if adt_def.is_enum() => {
+                format!("mk_{}_{}", datatype, adt_def.variant(variant).name)
+            }
+            _ => Env::constructor_name(datatype),
         }
     }
+
+    pub fn variant_selector_name(
+        &self,
+        datatype: &str,
+        ty: Ty<'tcx>,
+        variant: VariantIdx,
+        idx: usize,
+    ) -> String {
+        match ty.kind() {
+            TyKind::Adt(adt_def, _) if adt_def.is_enum() => {
+                format!("{}_{}_{}", datatype, adt_def.variant(variant).name, idx)
+            }
+            _ => Env::selector_name(datatype, idx),
+        }
+    }
+
+    /// A value of the given variant built from the values of its fields.
+    pub fn variant_value(
+        &self,
+        ty: Ty<'tcx>,
+        variant: VariantIdx,
+        values: Vec<String>,
+    ) -> Result<String, AnalysisError> {
+        let datatype = self.ty_to_sort(ty)?;
+        let constructor = self.variant_constructor_name(&datatype, ty, variant);
+        if values.is_empty() {
+            return Ok(constructor);
+        }
+        Ok(format!("({} {})", constructor, values.join(" ")))
+    }
+
+    /// Whether `value` is of the given variant.
+    pub fn variant_test(
+        &self,
+        ty: Ty<'tcx>,
+        variant: VariantIdx,
+        value: &str,
+    ) -> Result<String, AnalysisError> {
+        let datatype = self.ty_to_sort(ty)?;
+        Ok(format!(
+            "((_ is {}) {})",
+            self.variant_constructor_name(&datatype, ty, variant),
+            value
+        ))
+    }
+
+    /// The variant of an enum with the given name, e.g. `Some` of `Option<T>`.
+    pub fn variant_by_name(&self, ty: Ty<'tcx>, name: &str) -> Option<VariantIdx> {
+        let TyKind::Adt(adt_def, _) = ty.kind() else {
+            return None;
+        };
+        adt_def
+            .variants()
+            .iter_enumerated()
+            .find(|(_, variant)| variant.name.as_str() == name)
+            .map(|(idx, _)| idx)
+    }
+
     pub fn path_to_smt(&self, path: &Lir<'tcx>) -> Result<String, AnalysisError> {
         use LirKind::*;
 
@@ -152,12 +494,10 @@ impl<'tcx> Env<'tcx> {
 
     pub fn new_env_from_str(&self, name: String, span: Span) -> Result<Env<'tcx>, AnalysisError> {
         let name = self.get_unique_name(name, span);
-        Ok(Env::from(
+        Ok(Env {
             name,
-            self.path.clone(),
-            self.env_map.clone(),
-            self.vars.clone(),
-        ))
+            ..self.clone()
+        })
     }
 
     pub fn get_unique_name(&self, name: String, span: Span) -> String {
@@ -165,10 +505,22 @@ impl<'tcx> Env<'tcx> {
         format!("{}_{}", name, span_str)
     }
 
+    /// A name for a new constant: unlike `get_unique_name`, it differs each time the same
+    /// expression is evaluated, e.g. when a function is called twice.
+    pub fn fresh_name(&self, name: String, span: Span) -> String {
+        let count = self.fresh.get();
+        self.fresh.set(count + 1);
+        format!("{}_{}", self.get_unique_name(name, span), count)
+    }
+
     pub fn merge_env(&mut self, cond: &String, then_env: Env<'tcx>, else_env: Option<Env<'tcx>>) {
         let mut new_env_map = HashMap::new();
         let mut current_env_map = self.env_map.clone();
-        match else_env {
+        let else_ref_map = match &else_env {
+            Some(env) => env.ref_map.clone(),
+            None => self.ref_map.clone(),
+        };
+        match &else_env {
             Some(env) => {
                 for (var_id, lir) in current_env_map.iter_mut() {
                     let then_lir = then_env.env_map.get(var_id);
@@ -176,8 +528,11 @@ impl<'tcx> Env<'tcx> {
                     if let (Some(then_lir), Some(else_lir)) = (then_lir, else_lir) {
                         let then_constraint = then_lir.assume.clone().unwrap();
                         let else_constraint = else_lir.assume.clone().unwrap();
-                        let constraint =
-                            format!("(ite {} {} {})", cond, then_constraint, else_constraint);
+                        let constraint = if then_constraint == else_constraint {
+                            then_constraint
+                        } else {
+                            format!("(ite {} {} {})", cond, then_constraint, else_constraint)
+                        };
                         new_env_map.insert(
                             *var_id,
                             Lir::new(lir.kind.clone(), lir.expr.clone(), Some(constraint)),
@@ -190,12 +545,12 @@ impl<'tcx> Env<'tcx> {
                     let then_lir = then_env.env_map.get(var_id);
                     if let Some(then_lir) = then_lir {
                         let then_constraint = then_lir.assume.clone().unwrap();
-                        let constraint = format!(
-                            "(ite {} {} {})",
-                            cond,
-                            then_constraint,
-                            lir.assume.clone().unwrap()
-                        );
+                        let else_constraint = lir.assume.clone().unwrap();
+                        let constraint = if then_constraint == else_constraint {
+                            then_constraint
+                        } else {
+                            format!("(ite {} {} {})", cond, then_constraint, else_constraint)
+                        };
                         new_env_map.insert(
                             *var_id,
                             Lir::new(lir.kind.clone(), lir.expr.clone(), Some(constraint)),
@@ -205,36 +560,138 @@ impl<'tcx> Env<'tcx> {
             }
         }
         self.env_map = new_env_map;
+
+        let mut new_ref_map = HashMap::new();
+        for var_id in then_env.ref_map.keys().chain(else_ref_map.keys()) {
+            let place = match (then_env.ref_map.get(var_id), else_ref_map.get(var_id)) {
+                (Some(then_place), Some(else_place)) if then_place == else_place => {
+                    then_place.clone()
+                }
+                (Some(then_place), Some(else_place)) => Place::Ite {
+                    cond: cond.clone(),
+                    then: Box::new(then_place.clone()),
+                    else_: Box::new(else_place.clone()),
+                },
+                (Some(place), None) | (None, Some(place)) => place.clone(),
+                (None, None) => unreachable!(),
+            };
+            new_ref_map.insert(*var_id, place);
+        }
+        self.ref_map = new_ref_map;
+
+        let else_statics = match &else_env {
+            Some(env) => &env.statics,
+            None => &self.statics,
+        };
+        let mut new_statics = HashMap::new();
+        for (def_id, (ty, then_value)) in then_env.statics.iter() {
+            let value = match else_statics.get(def_id) {
+                Some((_, else_value)) if else_value != then_value => {
+                    format!("(ite {} {} {})", cond, then_value, else_value)
+                }
+                _ => then_value.clone(),
+            };
+            new_statics.insert(*def_id, (*ty, value));
+        }
+        self.statics = new_statics;
+
+        for branch in std::iter::once(&then_env).chain(else_env.iter()) {
+            for var in branch.vars.iter() {
+                if !self.vars.iter().any(|(_, name)| *name == var.1) {
+                    self.vars.push(var.clone());
+                }
+            }
+            for target in branch.fn_ptrs.iter() {
+                if !self.fn_ptrs.contains(target) {
+                    self.fn_ptrs.push(target.clone());
+                }
+            }
+        }
     }
 
+    /// Guards the path entries added after `path` by the branch condition `cond`, so that
+    /// they can be added to the path the branch was taken from.
     pub fn adapt_cond(&mut self, cond: &String, path: &Vec<Lir<'tcx>>) {
-        /*
-        println!("adapt_cond: {}", cond);
-        for lir in path.iter() {
-            println!("lir: {:?}", lir.assume.as_ref().expect("assume not exist"));
-        }
-        for lir in self.path.iter() {
-            println!("lir: {:?}", lir.assume.as_ref().expect("assume not exist"));
-        }*/
         for i in path.len()..self.len() {
-            self.path[i] = Lir::new_assume(
-                cond.clone(),
-                self.path[i].expr.clone(),
-                self.path[i].assume.clone(),
-            );
+            if let LirKind::Assume(constraint) = &self.path[i].kind {
+                self.path[i] = Lir::new_assume(
+                    format!("(=> {} {})", cond, constraint),
+                    self.path[i].expr.clone(),
+                    self.path[i].assume.clone(),
+                );
+            }
         }
     }
+
     pub fn merge_ite_env(
         &mut self,
         cond: &String,
         mut then_env: Env<'tcx>,
         mut else_env: Option<Env<'tcx>>,
     ) -> Result<(), AnalysisError> {
+        let depth = self.returns.len();
+        let then_ret = then_env.fold_returns(depth)?;
+        let else_ret = match else_env.as_mut() {
+            Some(env) => env.fold_returns(depth)?,
+            None => None,
+        };
         then_env.adapt_cond(&cond, &self.path);
         if let Some(env) = else_env.as_mut() {
             env.adapt_cond(&format!("(not {})", cond), &self.path);
         }
+        let mut branch_path = then_env.path.split_off(self.len());
+        if let Some(env) = else_env.as_mut() {
+            branch_path.extend(env.path.split_off(self.len()));
+        }
+        let both_diverged = match (&then_env.diverged, &else_env) {
+            (Some(expr), Some(env)) if env.diverged.is_some() => Some(expr.clone()),
+            _ => None,
+        };
         self.merge_env(&cond, then_env, else_env);
+        // What a branch assumes, including that it diverges, holds under its condition.
+        self.path.extend(branch_path);
+        if self.diverged.is_none() {
+            self.diverged = both_diverged;
+        }
+        self.merge_returns(cond, then_ret, else_ret);
         Ok(())
     }
+
+    /// Joins the returns taken in the branches of `cond`. Where neither returned, the path
+    /// goes on.
+    fn merge_returns(
+        &mut self,
+        cond: &String,
+        then_ret: Option<(String, String, Rc<RExpr<'tcx>>)>,
+        else_ret: Option<(String, String, Rc<RExpr<'tcx>>)>,
+    ) {
+        let (ret_cond, value, expr) = match (then_ret, else_ret) {
+            (Some((then_cond, then_value, expr)), Some((else_cond, else_value, _))) => {
+                let value = format!("(ite {} {} {})", cond, then_value, else_value);
+                if then_cond == "true" && else_cond == "true" {
+                    self.return_value(value, expr);
+                    return;
+                }
+                (
+                    format!("(ite {} {} {})", cond, then_cond, else_cond),
+                    value,
+                    expr,
+                )
+            }
+            (Some((then_cond, value, expr)), None) => {
+                (format!("(and {} {})", cond, then_cond), value, expr)
+            }
+            (None, Some((else_cond, value, expr))) => {
+                (format!("(and (not {}) {})", cond, else_cond), value, expr)
+            }
+            (None, None) => return,
+        };
+        self.returns.push(Return {
+            cond: ret_cond.clone(),
+            value,
+            expr: expr.clone(),
+            env: Box::new(self.clone()),
+        });
+        self.add_smt_command(format!("(not {})", ret_cond), expr);
+    }
 }
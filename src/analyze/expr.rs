@@ -2,13 +2,18 @@ use std::rc::Rc;
 
 use rustc_ast::ast::LitKind;
 use rustc_hir::Lit;
-use rustc_middle::mir::{BinOp, UnOp};
+use rustc_middle::mir::{self, BinOp, BorrowKind, UnOp};
 use rustc_middle::thir::*;
-use rustc_middle::ty::{Ty, TyKind};
+use rustc_middle::ty::adjustment::PointerCoercion as Coercion;
+use rustc_middle::ty::{FloatTy, GenericArgsRef, Mutability, ParamEnv, ScalarInt, Ty, TyKind};
+use rustc_span::def_id::DefId;
 
 use crate::analyze::core::{AnalysisError, AnalysisType};
+use crate::analyze::encoding::{Encoding, FloatEncoding, IntEncoding};
 use crate::analyze::Analyzer;
 use crate::analyze::Env;
+use crate::analyze::LirKind;
+use crate::analyze::Place;
 use crate::thir::rthir::*;
 
 impl<'tcx> Analyzer<'tcx> {
@@ -17,23 +22,13 @@ impl<'tcx> Analyzer<'tcx> {
         expr: Rc<RExpr<'tcx>>,
         env: &mut Env<'tcx>,
     ) -> Result<String, AnalysisError> {
-        // とりあえずInt literalのみ
-        if let RExprKind::Literal { lit, neg } = &expr.kind {
-            if let LitKind::Int(i, _) = lit.node {
-                Ok(if *neg {
-                    format!("-{}", i)
-                } else {
-                    format!("{}", i)
-                })
-            } else {
-                Err(AnalysisError::Unsupported(
-                    "Only Int literals are supported".to_string(),
-                ))
-            }
-        } else {
-            Err(AnalysisError::Unsupported(
+        match &expr.kind {
+            RExprKind::Literal { .. }
+            | RExprKind::NonHirLiteral { .. }
+            | RExprKind::ZstLiteral { .. } => self.expr_to_const(expr.clone(), env),
+            _ => Err(AnalysisError::Unsupported(
                 "Only literals are supported".to_string(),
-            ))
+            )),
         }
     }
 
@@ -62,16 +57,21 @@ impl<'tcx> Analyzer<'tcx> {
                 } = pat.as_ref()
                 {
                     match kind {
+                        Binding { ty, var, .. }
+                            if Analyzer::is_mut_ref(*ty) || self.is_cell_ref(*ty) =>
+                        {
+                            let place = self.ref_target(arg.clone(), env)?;
+                            env.ref_map.insert(*var, place);
+                        }
                         Binding { ty, var, .. } => {
                             let name = Analyzer::get_name_from_span(pat.span);
-                            env.add_param(name.clone(), ty.clone(), *var, pat.clone());
                             let arg_str = self.expr_to_const(arg.clone(), env)?;
+                            env.add_param(name.clone(), ty.clone(), *var, pat.clone());
                             env.assign_value(*var, arg_str, arg.clone());
                         }
                         _ => {
-                            return Err(AnalysisError::Unsupported(
-                                "Unsupported pattern in parameter".to_string(),
-                            ))
+                            let arg_str = self.expr_to_const(arg.clone(), env)?;
+                            self.bind_pattern(pat.clone(), arg_str, env)?;
                         }
                     }
                 } else {
@@ -83,20 +83,47 @@ impl<'tcx> Analyzer<'tcx> {
         }
         Ok(())
     }
-    pub fn binop_to_const(&self, op: BinOp, lhs: &str, rhs: &str) -> Result<String, AnalysisError> {
+    pub fn binop_to_const(
+        &self,
+        op: BinOp,
+        lhs: &str,
+        rhs: &str,
+        ty: Ty<'tcx>,
+    ) -> Result<String, AnalysisError> {
         use BinOp::*;
-        let bin_op = match op {
-            Add => "+",
-            Sub => "-",
-            Mul => "*",
-            Div => "/",
-            Rem => "%",
-            Eq => "=",
-            Ne => "distinct",
-            Lt => "<",
-            Le => "<=",
-            Gt => ">",
-            Ge => ">=",
+        if let TyKind::Float(_) = ty.kind() {
+            if self.encoding.float == FloatEncoding::Ieee {
+                return self.encoding.ieee_binop_to_const(op, lhs, rhs).ok_or(
+                    AnalysisError::Unsupported("Unsupported floating point operator".to_string()),
+                );
+            }
+        }
+        if let Some((width, signed)) = Encoding::int_width(ty) {
+            if self.encoding.int == IntEncoding::BitVector {
+                return self
+                    .encoding
+                    .bv_binop_to_const(op, lhs, rhs, width, signed)
+                    .ok_or(AnalysisError::Unsupported(
+                        "Unsupported bit-vector operator".to_string(),
+                    ));
+            }
+        }
+        let bin_op = match (op, ty.kind()) {
+            (BitAnd, TyKind::Bool) => "and",
+            (BitOr, TyKind::Bool) => "or",
+            (BitXor, TyKind::Bool) => "xor",
+            (Add, _) => "+",
+            (Sub, _) => "-",
+            (Mul, _) => "*",
+            (Div, TyKind::Float(_)) => "/",
+            (Div, _) => "div",
+            (Rem, TyKind::Int(_) | TyKind::Uint(_)) => "mod",
+            (Eq, _) => "=",
+            (Ne, _) => "distinct",
+            (Lt, _) => "<",
+            (Le, _) => "<=",
+            (Gt, _) => ">",
+            (Ge, _) => ">=",
             _ => {
                 return Err(AnalysisError::Unsupported(
                     "Unsupported operator".to_string(),
@@ -106,6 +133,25 @@ impl<'tcx> Analyzer<'tcx> {
         Ok(format!("({} {} {})", bin_op, lhs, rhs))
     }
 
+    pub fn binary_to_const(
+        &self,
+        op: BinOp,
+        lhs: Rc<RExpr<'tcx>>,
+        rhs: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let lhs_str = self.expr_to_const(lhs.clone(), env)?;
+        let mut rhs_str = self.expr_to_const(rhs.clone(), env)?;
+        // The shift amount may have any integer type; bring it to the width of the lhs.
+        if matches!(op, BinOp::Shl | BinOp::Shr) && lhs.ty != rhs.ty {
+            rhs_str = self.cast_to_const(&rhs_str, rhs.ty, lhs.ty)?;
+        }
+        if matches!(op, BinOp::Div | BinOp::Rem) && lhs.ty.is_integral() {
+            self.verify_divisor(&rhs_str, rhs.ty, rhs.span, env)?;
+        }
+        self.binop_to_const(op, &lhs_str, &rhs_str, lhs.ty)
+    }
+
     pub fn analyze_let_stmt(
         &self,
         pattern: Rc<RExpr<'tcx>>,
@@ -115,6 +161,17 @@ impl<'tcx> Analyzer<'tcx> {
     ) -> Result<(), AnalysisError> {
         if let RExprKind::Pat { kind, .. } = &pattern.kind {
             match kind {
+                RPatKind::AscribeUserType { subpattern, .. } => {
+                    return self.analyze_let_stmt(subpattern.clone(), init, else_block, env)
+                }
+                RPatKind::Binding { ty, var, .. }
+                    if Analyzer::is_mut_ref(*ty) || self.is_cell_ref(*ty) =>
+                {
+                    if let Some(init) = init {
+                        let place = self.ref_target(init, env)?;
+                        env.ref_map.insert(*var, place);
+                    }
+                }
                 RPatKind::Binding { ty, var, .. } => {
                     let name = Analyzer::get_name_from_span(pattern.span);
                     env.add_param(name.clone(), ty.clone(), *var, pattern.clone());
@@ -129,6 +186,9 @@ impl<'tcx> Analyzer<'tcx> {
                                 AnalysisError::RandFunctions => {
                                     let name = format! {"rand_{}", Analyzer::get_name_from_span(pattern.span)};
                                     env.add_random_var(ty.clone(), name.clone());
+                                    if let Some(range) = self.int_range_to_const(&name, *ty) {
+                                        env.add_smt_command(range, pattern.clone());
+                                    }
                                     env.assign_value(*var, name.clone(), pattern.clone());
                                 }
                                 _ => return Err(err),
@@ -136,20 +196,13 @@ impl<'tcx> Analyzer<'tcx> {
                         }
                     }
                 }
-                _ => {
-                    return Err(AnalysisError::Unsupported(
-                        format!("Unsupported pattern in let statement {:?}", kind).to_string(),
-                    ))
-                }
+                _ => return self.analyze_let_pattern(pattern.clone(), init, else_block, env),
             }
         } else {
             return Err(AnalysisError::Unsupported(
                 "Unsupported expression in let statement".to_string(),
             ));
         }
-        if let Some(else_block) = else_block {
-            self.analyze_body(else_block.clone(), env)?;
-        }
         Ok(())
     }
 
@@ -159,13 +212,16 @@ impl<'tcx> Analyzer<'tcx> {
         rhs: Rc<RExpr<'tcx>>,
         env: &mut Env<'tcx>,
     ) -> Result<(), AnalysisError> {
+        if Analyzer::is_mut_ref(lhs.ty) || self.is_cell_ref(lhs.ty) {
+            if let RExprKind::VarRef { id } = lhs.kind {
+                let place = self.ref_target(rhs, env)?;
+                env.ref_map.insert(id, place);
+                return Ok(());
+            }
+        }
         let rhs_str = self.expr_to_const(rhs.clone(), env)?;
-        let var = env
-            .env_map
-            .get_mut(&Analyzer::expr_to_var_id(lhs))
-            .expect("assign target variant not found");
-        var.assume = Some(rhs_str);
-        Ok(())
+        let place = self.expr_to_place(lhs, env)?;
+        env.write_place(&place, rhs_str)
     }
 
     pub fn analyze_assign_op(
@@ -175,56 +231,32 @@ impl<'tcx> Analyzer<'tcx> {
         rhs: Rc<RExpr<'tcx>>,
         env: &mut Env<'tcx>,
     ) -> Result<(), AnalysisError> {
-        let lhs_str = self.expr_to_const(lhs.clone(), env)?;
-        let rhs_str = self.expr_to_const(rhs.clone(), env)?;
-        let bin_op_str = self.bin_op_to_smt(op)?;
-        let constraint = format!("({} {} {})", bin_op_str, lhs_str, rhs_str);
-        let var = env
-            .env_map
-            .get_mut(&Analyzer::expr_to_var_id(lhs))
-            .expect("assign target variant not found");
-        var.assume = Some(constraint);
-        Ok(())
+        let constraint = self.binary_to_const(op, lhs.clone(), rhs.clone(), env)?;
+        let place = self.expr_to_place(lhs, env)?;
+        env.write_place(&place, constraint)
     }
 
-    pub fn analyze_var_ref(
+    /// `lhs && rhs` and `lhs || rhs`: the right operand is only evaluated on the path
+    /// where the left one does not decide the result.
+    pub fn short_circuit_to_const(
         &self,
-        expr: Rc<RExpr<'tcx>>,
+        op: LogicalOp,
+        lhs: Rc<RExpr<'tcx>>,
+        rhs: Rc<RExpr<'tcx>>,
         env: &mut Env<'tcx>,
-    ) -> Result<(), AnalysisError> {
-        let var = env
-            .env_map
-            .get(&Analyzer::expr_to_var_id(expr.clone()))
-            .expect("Variable not found")
-            .assume
-            .clone()
-            .unwrap_or_default();
-
-        Ok(())
-    }
-
-    pub fn bin_op_to_smt(&self, op: BinOp) -> Result<String, AnalysisError> {
-        use BinOp::*;
-        let bin_op_str = match op {
-            Add => "+",
-            Sub => "-",
-            Mul => "*",
-            Div => "div",
-            Rem => "mod",
-            Eq => "=",
-            Lt => "<",
-            Le => "<=",
-            Gt => ">",
-            Ge => ">=",
-            Ne => "distinct",
-            _ => {
-                return Err(AnalysisError::Unsupported(
-                    "Unsupported operator".to_string(),
-                ))
-            }
+    ) -> Result<String, AnalysisError> {
+        let lhs_str = self.expr_to_const(lhs.clone(), env)?;
+        let cond = match op {
+            LogicalOp::And => lhs_str.clone(),
+            LogicalOp::Or => format!("(not {})", lhs_str),
         };
-        Ok(bin_op_str.to_string())
+        let mut rhs_env = env.new_env_from_str("rhs".to_string(), rhs.span)?;
+        rhs_env.add_smt_command(cond.clone(), lhs.clone());
+        let rhs_str = self.expr_to_const(rhs.clone(), &mut rhs_env)?;
+        env.merge_ite_env(&cond, rhs_env, None)?;
+        self.logical_op_to_const(op, &lhs_str, &rhs_str)
     }
+
     pub fn logical_op_to_const(
         &self,
         op: LogicalOp,
@@ -239,11 +271,21 @@ impl<'tcx> Analyzer<'tcx> {
         Ok(format!("({} {} {})", logical_op, lhs, rhs))
     }
 
-    pub fn unop_to_const(&self, op: UnOp, arg: &str) -> Result<String, AnalysisError> {
+    pub fn unop_to_const(
+        &self,
+        op: UnOp,
+        arg: &str,
+        ty: Ty<'tcx>,
+    ) -> Result<String, AnalysisError> {
         use rustc_middle::mir::UnOp::*;
-        let un_op = match op {
-            Not => "not",
-            Neg => "-",
+        let un_op = match (op, ty.kind()) {
+            (Neg, TyKind::Float(_)) => return Ok(self.encoding.float_neg_to_const(arg)),
+            (Not, TyKind::Bool) => "not",
+            (Neg | Not, TyKind::Int(_) | TyKind::Uint(_)) => {
+                return self.encoding.int_unop_to_const(op == Neg, arg).ok_or(
+                    AnalysisError::Unsupported("Unsupported integer unary operator".to_string()),
+                )
+            }
             _ => {
                 return Err(AnalysisError::Unsupported(
                     "Unsupported operator in unary expression".to_string(),
@@ -278,20 +320,16 @@ impl<'tcx> Analyzer<'tcx> {
         else_opt: Option<Rc<RExpr<'tcx>>>,
         env: &mut Env<'tcx>,
     ) -> Result<String, AnalysisError> {
-        let mut cond_env = env.new_env_from_str("cond".to_string(), cond.span)?;
-        let cond_str = self.expr_to_const(cond.clone(), &mut cond_env)?;
-        cond_env.add_smt_command(cond_str.clone(), cond.clone());
+        let cond_str = self.expr_to_const(cond.clone(), env)?;
 
         let mut then_env = env.new_env_from_str("then".to_string(), then.span)?;
         then_env.add_smt_command(cond_str.clone(), cond.clone());
         let then_str = self.expr_to_const(then.clone(), &mut then_env)?;
-        then_env.add_smt_command(then_str.clone(), then.clone());
 
         let else_expr = else_opt.expect("No else expression in if statement");
         let mut else_env = env.new_env_from_str("else".to_string(), else_expr.span)?;
         else_env.add_smt_command(format!("(not {})", cond_str.clone()), cond.clone());
         let else_str = self.expr_to_const(else_expr.clone(), &mut else_env)?;
-        else_env.add_smt_command(else_str.clone(), else_expr.clone());
 
         env.merge_ite_env(&cond_str, then_env, Some(else_env))?;
 
@@ -307,20 +345,21 @@ impl<'tcx> Analyzer<'tcx> {
 
         println!("expr_to_const: {:?}", expr.kind);
         match &expr.kind {
-            Literal { lit, neg } => Ok(self.literal_to_const(lit, *neg)?),
-            Binary { op, lhs, rhs } => {
-                let lhs = self.expr_to_const(lhs.clone(), env)?;
-                let rhs = self.expr_to_const(rhs.clone(), env)?;
-                Ok(self.binop_to_const(*op, &lhs, &rhs)?)
-            }
+            Literal { lit, neg } => self.literal_to_const(lit, *neg, expr.ty),
+            NonHirLiteral { lit, .. } => self.scalar_int_to_const(*lit, expr.ty),
+            ZstLiteral { .. } => self.zst_to_const(expr.ty, env),
+            Binary { op, lhs, rhs } => self.binary_to_const(*op, lhs.clone(), rhs.clone(), env),
             LogicalOp { op, lhs, rhs } => {
-                let lhs = self.expr_to_const(lhs.clone(), env)?;
-                let rhs = self.expr_to_const(rhs.clone(), env)?;
-                Ok(self.logical_op_to_const(*op, &lhs, &rhs)?)
+                self.short_circuit_to_const(*op, lhs.clone(), rhs.clone(), env)
+            }
+            Cast { source } => {
+                let source_str = self.expr_to_const(source.clone(), env)?;
+                self.cast_to_const(&source_str, source.ty, expr.ty)
             }
             Unary { op, arg } => {
+                let ty = arg.ty;
                 let arg = self.expr_to_const(arg.clone(), env)?;
-                Ok(self.unop_to_const(*op, &arg)?)
+                Ok(self.unop_to_const(*op, &arg, ty)?)
             }
             //Call { ty, args, .. } => Ok(self.fn_to_expr(*ty, args.clone(), expr.clone(), env)?),
             If {
@@ -329,16 +368,297 @@ impl<'tcx> Analyzer<'tcx> {
                 else_opt,
             } => Ok(self.if_to_const(cond.clone(), then.clone(), else_opt.clone(), env)?),
             Block { .. } => self.block_to_const(expr.clone(), env),
+            VarRef { .. } if self.is_cell_ref(expr.ty) => {
+                let place = self.ref_target(expr.clone(), env)?;
+                env.read_place(&place)
+            }
             VarRef { id } => self.var_ref_to_const(*id, env),
-            Call { ty, args, .. } => Ok(self
-                .fn_to_const(*ty, args.clone(), expr.clone(), env)?
-                .to_string()),
+            UpvarRef {
+                closure_def_id,
+                var_hir_id,
+            } => match self.upvar_place(*closure_def_id, *var_hir_id, env)? {
+                Some(place) => env.read_place(&place),
+                None => self.var_ref_to_const(*var_hir_id, env),
+            },
+            Closure { upvars, .. } => self.closure_to_const(expr.ty, upvars, env),
+            Assign { lhs, rhs } => {
+                self.analyze_assign(lhs.clone(), rhs.clone(), env)?;
+                Ok("unit".to_string())
+            }
+            AssignOp { op, lhs, rhs } => {
+                self.analyze_assign_op(*op, lhs.clone(), rhs.clone(), env)?;
+                Ok("unit".to_string())
+            }
+            Deref { arg } if Analyzer::is_mut_ref(arg.ty) || self.is_cell_ref(arg.ty) => {
+                let place = self.ref_target(arg.clone(), env)?;
+                env.read_place(&place)
+            }
+            // Shared references are encoded as the value they point to.
+            Deref { arg } => self.expr_to_const(arg.clone(), env),
+            Borrow {
+                borrow_kind: BorrowKind::Mut { .. },
+                ..
+            } => Err(AnalysisError::Unsupported(
+                "Mutable borrow used as a value".to_string(),
+            )),
+            Borrow { arg, .. } => self.expr_to_const(arg.clone(), env),
+            Field { lhs, name, .. } => {
+                let datatype = env.ty_to_sort(lhs.ty)?;
+                let lhs = self.expr_to_const(lhs.clone(), env)?;
+                Ok(format!(
+                    "({} {})",
+                    Env::selector_name(&datatype, name.as_usize()),
+                    lhs
+                ))
+            }
+            Index { lhs, index } => {
+                let lhs_ty = lhs.ty;
+                let lhs = self.expr_to_const(lhs.clone(), env)?;
+                let index = self.expr_to_const(index.clone(), env)?;
+                self.verify_index(lhs_ty, &index, expr.span, env)?;
+                Ok(format!("(select {} {})", lhs, index))
+            }
+            Tuple { fields } => self.aggregate_to_const(expr.ty, fields, None, env),
+            Adt {
+                adt_def,
+                variant_index,
+                fields,
+                ..
+            } if adt_def.is_enum() => {
+                let mut fields = fields.to_vec();
+                fields.sort_by_key(|(idx, _)| *idx);
+                let mut values = Vec::new();
+                for (_, field) in fields {
+                    values.push(self.expr_to_const(field, env)?);
+                }
+                env.variant_value(expr.ty, *variant_index, values)
+            }
+            Adt { fields, base, .. } => {
+                let (idxs, fields): (Vec<usize>, Vec<Rc<RExpr<'tcx>>>) = fields
+                    .iter()
+                    .map(|(idx, field)| (idx.as_usize(), field.clone()))
+                    .unzip();
+                self.aggregate_to_const(expr.ty, &fields, Some((idxs, base.clone())), env)
+            }
+            Array { fields } => {
+                let sort = env.ty_to_sort(expr.ty)?;
+                let mut array = String::new();
+                for (idx, field) in fields.iter().enumerate() {
+                    let value = self.expr_to_const(field.clone(), env)?;
+                    array = if idx == 0 {
+                        format!("((as const {}) {})", sort, value)
+                    } else {
+                        format!(
+                            "(store {} {} {})",
+                            array,
+                            self.encoding.int_to_const(idx as u128, false, 64),
+                            value
+                        )
+                    };
+                }
+                Ok(array)
+            }
+            Repeat { value, .. } => {
+                let sort = env.ty_to_sort(expr.ty)?;
+                let value = self.expr_to_const(value.clone(), env)?;
+                Ok(format!("((as const {}) {})", sort, value))
+            }
+            PlaceTypeAscription { source, .. } | ValueTypeAscription { source, .. } => {
+                self.expr_to_const(source.clone(), env)
+            }
+            Call { ty, args, .. } => {
+                let value = self.fn_to_const(*ty, args.clone(), expr.clone(), env)?;
+                if Analyzer::diverges(&expr) {
+                    env.diverge(expr.clone());
+                }
+                Ok(value)
+            }
+            NeverToAny { source } => {
+                self.expr_to_const(source.clone(), env)?;
+                self.never_to_const(expr.clone(), env)
+            }
+            LetBinding {
+                expr: scrutinee,
+                pat,
+            } => self.let_binding_to_const(scrutinee.clone(), pat.clone(), env),
+            Match { scrutinee, arms } => self.match_to_const(
+                scrutinee.clone(),
+                arms,
+                &|analyzer, body, env| analyzer.expr_to_const(body, env),
+                env,
+            ),
+            Return { value } => {
+                let value = match value {
+                    Some(value) => self.expr_to_const(value.clone(), env)?,
+                    None => self.zst_to_const(self.tcx.types.unit, env)?,
+                };
+                env.return_value(value, expr.clone());
+                self.zst_to_const(self.tcx.types.unit, env)
+            }
+            NamedConst { def_id, args, .. } => {
+                self.named_const_to_const(*def_id, args, expr.ty, env)
+            }
+            ConstParam { def_id, .. } => self.const_param_to_const(*def_id, expr.clone(), env),
+            StaticRef { def_id } => self.static_to_const(*def_id, env),
+            ConstBlock { did, args } => self.const_body_to_const(*did, args, env),
+            // The concrete value behind a trait object is forgotten.
+            PointerCoercion { .. } if expr.ty.is_ref() && expr.ty.peel_refs().is_trait() => {
+                let name = env.fresh_name("dyn".to_string(), expr.span);
+                env.add_random_var(expr.ty, name.clone());
+                Ok(name)
+            }
+            PointerCoercion {
+                cast: Coercion::ReifyFnPointer | Coercion::ClosureFnPointer(_),
+                source,
+            } => self.reify_to_const(expr.ty, source.clone(), env),
             _ => Err(AnalysisError::Unsupported(
                 format!("Unsupported expression {:?}", expr.kind).to_string(),
             )),
         }
     }
 
+    /// Local constants, including associated constants of `impl` blocks, are inlined
+    /// like a call without arguments. Constants of other crates, e.g. `u32::MAX`, are
+    /// evaluated by the compiler.
+    pub fn named_const_to_const(
+        &self,
+        def_id: DefId,
+        args: GenericArgsRef<'tcx>,
+        ty: Ty<'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        // The keys of `thread_local!` stand for their slots.
+        if let Some((_, value)) = env.statics.get(&def_id) {
+            return Ok(value.clone());
+        }
+        // Associated constants used through a trait are those of the implementation.
+        let (def_id, args, _) = self.resolve_fn(def_id, args);
+        if !def_id.is_local() {
+            let value = mir::Const::Unevaluated(mir::UnevaluatedConst::new(def_id, args), ty);
+            return self.mir_const_to_const(value, ty);
+        }
+        self.const_body_to_const(def_id, args, env)
+    }
+
+    /// The body of a local constant or inline `const` block, inlined.
+    pub fn const_body_to_const(
+        &self,
+        def_id: DefId,
+        args: GenericArgsRef<'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        match self.get_local_instance(&def_id, args)? {
+            Some(rthir) => match &rthir.body {
+                Some(body) => self.expr_to_const(body.clone(), env),
+                None => Err(AnalysisError::Unsupported(
+                    "No RThir body Found".to_string(),
+                )),
+            },
+            None => Err(AnalysisError::Unsupported(format!(
+                "Unsupported constant {}",
+                self.tcx.def_path_str(def_id)
+            ))),
+        }
+    }
+
+    /// A `const` generic parameter of a body analyzed for any arguments: an arbitrary
+    /// value of its type, the same at every use.
+    pub fn const_param_to_const(
+        &self,
+        def_id: DefId,
+        expr: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let name = format!(
+            "const_{}",
+            Analyzer::get_name_from_span(self.tcx.def_span(def_id))
+        );
+        if !env.vars.iter().any(|(_, var)| *var == name) {
+            env.add_random_var(expr.ty, name.clone());
+            if let Some(range) = self.int_range_to_const(&name, expr.ty) {
+                env.add_smt_command(range, expr);
+            }
+        }
+        Ok(name)
+    }
+
+    /// A static of the global state has its current value. Any other immutable `static`
+    /// is a constant; its initializer is inlined. The expression is a reference to it,
+    /// which is encoded as the value.
+    pub fn static_to_const(
+        &self,
+        def_id: DefId,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        if let Some((_, value)) = env.statics.get(&def_id) {
+            return Ok(value.clone());
+        }
+        if self.tcx.static_mutability(def_id) != Some(Mutability::Not) {
+            return Err(AnalysisError::Unsupported(format!(
+                "Mutable static {} is not supported",
+                self.tcx.def_path_str(def_id)
+            )));
+        }
+        match self.get_local_fn(&def_id) {
+            Some(rthir) => match &rthir.body {
+                Some(body) => self.expr_to_const(body.clone(), env),
+                None => Err(AnalysisError::Unsupported(
+                    "No RThir body Found".to_string(),
+                )),
+            },
+            None => Err(AnalysisError::Unsupported(format!(
+                "Static {} of another crate is not supported",
+                self.tcx.def_path_str(def_id)
+            ))),
+        }
+    }
+
+    /// Builds a struct or tuple value; fields missing from a struct expression are
+    /// taken from its base (`S { x: 1, ..base }`).
+    pub fn aggregate_to_const(
+        &self,
+        ty: Ty<'tcx>,
+        fields: &[Rc<RExpr<'tcx>>],
+        adt_fields: Option<(Vec<usize>, Option<Rc<RExpr<'tcx>>>)>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        if fields.is_empty() && adt_fields.is_none() {
+            return Ok("unit".to_string());
+        }
+        let datatype = env.ty_to_sort(ty)?;
+        let field_count = env.field_tys(ty).len();
+        let mut values = vec![None; field_count];
+        let (idxs, base) = adt_fields.unwrap_or(((0..fields.len()).collect(), None));
+        for (idx, field) in idxs.into_iter().zip(fields.iter()) {
+            values[idx] = Some(self.expr_to_const(field.clone(), env)?);
+        }
+        let base = match base {
+            Some(base) => Some(self.expr_to_const(base, env)?),
+            None => None,
+        };
+        let values = values
+            .into_iter()
+            .enumerate()
+            .map(|(idx, value)| match (value, &base) {
+                (Some(value), _) => Ok(value),
+                (None, Some(base)) => {
+                    Ok(format!("({} {})", Env::selector_name(&datatype, idx), base))
+                }
+                (None, None) => Err(AnalysisError::Unsupported(
+                    "Missing field in struct expression".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<String>, AnalysisError>>()?;
+        if values.is_empty() {
+            return Ok(Env::constructor_name(&datatype));
+        }
+        Ok(format!(
+            "({} {})",
+            Env::constructor_name(&datatype),
+            values.join(" ")
+        ))
+    }
+
     pub fn block_to_const(
         &self,
         block: Rc<RExpr<'tcx>>,
@@ -347,8 +667,10 @@ impl<'tcx> Analyzer<'tcx> {
         let mut res = String::new();
         if let RExprKind::Block { stmts, expr } = &block.kind {
             for stmt in stmts {
-                if let AnalysisType::Return(value) = self.analyze_expr(stmt.clone(), env)? {
-                    return Ok(value.expect("returned but no value"));
+                self.analyze_expr(stmt.clone(), env)?;
+                // The rest of the block is unreachable; the value returned is the call's.
+                if let Some((value, _)) = &env.returned {
+                    return Ok(value.clone());
                 }
             }
             if let Some(expr) = expr {
@@ -362,17 +684,129 @@ impl<'tcx> Analyzer<'tcx> {
         Ok(res)
     }
 
-    pub fn literal_to_const(&self, lit: &Lit, neg: bool) -> Result<String, AnalysisError> {
-        if let LitKind::Int(i, _) = lit.node {
-            Ok(if neg {
-                format!("-{}", i)
-            } else {
-                format!("{}", i)
-            })
-        } else {
-            Err(AnalysisError::Unsupported(
-                "Only Int literals are supported".to_string(),
-            ))
+    pub fn literal_to_const(
+        &self,
+        lit: &Lit,
+        neg: bool,
+        ty: Ty<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let value = match lit.node {
+            LitKind::Int(i, _) => i.get(),
+            LitKind::Float(symbol, _) => {
+                let value = symbol.as_str().replace('_', "");
+                let unparsable =
+                    || AnalysisError::Unsupported(format!("Unsupported float literal {}", symbol));
+                let (value, float_ty) = match ty.kind() {
+                    TyKind::Float(FloatTy::F32) => (
+                        value.parse::<f32>().map_err(|_| unparsable())? as f64,
+                        FloatTy::F32,
+                    ),
+                    _ => (
+                        value.parse::<f64>().map_err(|_| unparsable())?,
+                        FloatTy::F64,
+                    ),
+                };
+                // Negate before encoding so that `-0.0` keeps its sign bit.
+                let value = if neg { -value } else { value };
+                return Ok(self.encoding.float_to_const(value, float_ty));
+            }
+            LitKind::Bool(b) => return Ok(format!("{}", b)),
+            LitKind::Str(symbol, _) => return Ok(Encoding::str_to_const(symbol.as_str())),
+            LitKind::Char(c) => c as u128,
+            LitKind::Byte(b) => b as u128,
+            _ => {
+                return Err(AnalysisError::Unsupported(format!(
+                    "Unsupported literal {:?}",
+                    lit.node
+                )))
+            }
+        };
+        let (width, _) = Encoding::int_width(ty).ok_or(AnalysisError::Unsupported(format!(
+            "Unsupported literal type {:?}",
+            ty
+        )))?;
+        Ok(self.encoding.int_to_const(value, neg, width))
+    }
+
+    /// Evaluates a constant, e.g. `u32::MAX`, to a scalar.
+    pub fn mir_const_to_const(
+        &self,
+        value: mir::Const<'tcx>,
+        ty: Ty<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let scalar = value
+            .try_eval_scalar_int(self.tcx, ParamEnv::reveal_all())
+            .ok_or(AnalysisError::Unsupported(format!(
+                "Unsupported constant {:?}",
+                value
+            )))?;
+        self.scalar_int_to_const(scalar, ty)
+    }
+
+    pub fn scalar_int_to_const(
+        &self,
+        lit: ScalarInt,
+        ty: Ty<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        match ty.kind() {
+            TyKind::Bool => lit
+                .try_to_bool()
+                .map(|b| format!("{}", b))
+                .map_err(|_| AnalysisError::Unsupported("Invalid bool constant".to_string())),
+            TyKind::Int(_) => {
+                let i = lit.to_int(lit.size());
+                Ok(self
+                    .encoding
+                    .int_to_const(i.unsigned_abs(), i < 0, lit.size().bits()))
+            }
+            TyKind::Uint(_) | TyKind::Char => {
+                Ok(self
+                    .encoding
+                    .int_to_const(lit.to_uint(lit.size()), false, lit.size().bits()))
+            }
+            TyKind::Float(FloatTy::F32) => Ok(self
+                .encoding
+                .float_to_const(f32::from_bits(lit.to_u32()) as f64, FloatTy::F32)),
+            TyKind::Float(FloatTy::F64) => Ok(self
+                .encoding
+                .float_to_const(f64::from_bits(lit.to_u64()), FloatTy::F64)),
+            _ => Err(AnalysisError::Unsupported(format!(
+                "Unsupported constant of type {:?}",
+                ty
+            ))),
+        }
+    }
+
+    pub fn zst_to_const(&self, ty: Ty<'tcx>, env: &mut Env<'tcx>) -> Result<String, AnalysisError> {
+        match ty.kind() {
+            TyKind::Tuple(fields) if fields.is_empty() => Ok("unit".to_string()),
+            // The only inhabited variant, e.g. `None` of `Option<Infallible>`.
+            TyKind::Adt(adt_def, args) if adt_def.is_enum() => {
+                let param_env = ParamEnv::reveal_all();
+                let Some((variant_idx, variant)) =
+                    adt_def.variants().iter_enumerated().find(|(_, variant)| {
+                        variant.fields.iter().all(|field| {
+                            !field
+                                .ty(self.tcx, args)
+                                .is_privately_uninhabited(self.tcx, param_env)
+                        })
+                    })
+                else {
+                    return Ok("unit".to_string());
+                };
+                let values = variant
+                    .fields
+                    .iter()
+                    .map(|field| self.zst_to_const(field.ty(self.tcx, args), env))
+                    .collect::<Result<Vec<String>, AnalysisError>>()?;
+                env.variant_value(ty, variant_idx, values)
+            }
+            TyKind::Adt(..) => Ok(Env::constructor_name(&env.ty_to_sort(ty)?)),
+            TyKind::FnDef(..) => Ok("unit".to_string()),
+            _ => Err(AnalysisError::Unsupported(format!(
+                "Unsupported zero-sized constant of type {:?}",
+                ty
+            ))),
         }
     }
 
@@ -383,9 +817,7 @@ impl<'tcx> Analyzer<'tcx> {
         else_opt: Option<Rc<RExpr<'tcx>>>,
         env: &mut Env<'tcx>,
     ) -> Result<AnalysisType<'tcx>, AnalysisError> {
-        let mut cond_env = env.new_env_from_str("cond".to_string(), cond.span)?;
         let cond_str = self.expr_to_const(cond.clone(), env)?;
-        cond_env.add_smt_command(cond_str.clone(), cond.clone());
 
         let mut then_env = env.new_env_from_str("then".to_string(), then.span)?;
         then_env.add_smt_command(cond_str.clone(), cond.clone());
@@ -395,7 +827,13 @@ impl<'tcx> Analyzer<'tcx> {
         if let Some(else_expr) = else_opt {
             let mut now_else_env = env.new_env_from_str("else".to_string(), else_expr.span)?;
             now_else_env.add_smt_command(format!("(not {})", cond_str.clone()), cond.clone());
-            self.analyze_block(else_expr.clone(), &mut now_else_env)?;
+            match else_expr.kind {
+                // `else if`
+                RExprKind::If { .. } => {
+                    self.analyze_expr(else_expr.clone(), &mut now_else_env)?;
+                }
+                _ => self.analyze_block(else_expr.clone(), &mut now_else_env)?,
+            }
             else_env = Some(now_else_env);
         }
         env.merge_ite_env(&cond_str, then_env, else_env)?;
@@ -403,14 +841,133 @@ impl<'tcx> Analyzer<'tcx> {
         Ok(AnalysisType::Other)
     }
 
+    /// The condition and body of a `while` loop, which is lowered to
+    /// `loop { if cond { body } else { break } }`.
+    pub fn while_parts(body: &RExpr<'tcx>) -> Option<(Rc<RExpr<'tcx>>, Rc<RExpr<'tcx>>)> {
+        let RExprKind::Block {
+            stmts,
+            expr: Some(expr),
+        } = &body.kind
+        else {
+            return None;
+        };
+        match &expr.kind {
+            RExprKind::If {
+                cond,
+                then,
+                else_opt: Some(else_expr),
+            } if stmts.is_empty() && Analyzer::is_break(else_expr) => {
+                Some((cond.clone(), then.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_break(expr: &RExpr<'tcx>) -> bool {
+        match &expr.kind {
+            RExprKind::Break { value: None, .. } => true,
+            RExprKind::NeverToAny { source } => Analyzer::is_break(source),
+            RExprKind::Block {
+                stmts,
+                expr: Some(expr),
+            } if stmts.is_empty() => Analyzer::is_break(expr),
+            RExprKind::Block { stmts, expr: None } if stmts.len() == 1 => {
+                Analyzer::is_break(&stmts[0])
+            }
+            _ => false,
+        }
+    }
+
+    /// A `while` loop is analyzed as one arbitrary iteration: the variables and statics
+    /// its body assigns are havocked, the body is checked under the condition, and the
+    /// loop is left where the condition fails.
+    pub fn analyze_while(
+        &self,
+        cond: Rc<RExpr<'tcx>>,
+        body: Rc<RExpr<'tcx>>,
+        expr: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<(), AnalysisError> {
+        // The first iteration tells which variables the body assigns.
+        let mut first_env = env.new_env_from_str("first".to_string(), expr.span)?;
+        let first_cond = self.expr_to_const(cond.clone(), &mut first_env)?;
+        first_env.add_smt_command(first_cond, cond.clone());
+        self.analyze_block(body.clone(), &mut first_env)?;
+        if first_env.returned.is_some() || first_env.returns.len() > env.returns.len() {
+            return Err(AnalysisError::Unsupported(
+                "Returning from inside a loop is not supported".to_string(),
+            ));
+        }
+        // Writes that leave a variable as it was are found in the syntax of the loop.
+        let mut written = Vec::new();
+        self.written_places(&cond, env, &mut written);
+        self.written_places(&body, env, &mut written);
+        let mut assigned: Vec<LocalVarId> = env
+            .env_map
+            .iter()
+            .filter(|(id, lir)| {
+                written.contains(&Place::Var(**id))
+                    || first_env
+                        .env_map
+                        .get(id)
+                        .is_some_and(|first| first.assume != lir.assume)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        assigned.sort_by_key(|id| id.0);
+        for id in assigned {
+            let LirKind::Declaration { name, ty } = env.env_map[&id].kind.clone() else {
+                continue;
+            };
+            let havoc = format!("{}_{}", env.fresh_name("loop".to_string(), expr.span), name);
+            env.add_random_var(ty, havoc.clone());
+            if let Some(range) = self.int_range_to_const(&havoc, ty) {
+                env.add_smt_command(range, expr.clone());
+            }
+            env.assign_value(id, havoc, expr.clone());
+        }
+        let mut assigned_statics: Vec<DefId> = env
+            .statics
+            .iter()
+            .filter(|(def_id, (_, value))| {
+                written.contains(&Place::Static(**def_id)) || first_env.statics[*def_id].1 != *value
+            })
+            .map(|(def_id, _)| *def_id)
+            .collect();
+        assigned_statics.sort_by_key(|def_id| def_id.index);
+        for def_id in assigned_statics {
+            self.havoc_global(def_id, "loop", expr.clone(), env);
+        }
+
+        println!(
+            "Checking an arbitrary iteration of the loop at {:?}",
+            expr.span
+        );
+        let cond_str = self.expr_to_const(cond.clone(), env)?;
+        let mut iter_env = env.new_env_from_str("loop".to_string(), expr.span)?;
+        iter_env.add_smt_command(cond_str.clone(), cond.clone());
+        self.analyze_block(body, &mut iter_env)?;
+        env.add_smt_command(format!("(not {})", cond_str), cond);
+        Ok(())
+    }
+
     pub fn analyze_block(
         &self,
         block: Rc<RExpr<'tcx>>,
         env: &mut Env<'tcx>,
     ) -> Result<(), AnalysisError> {
-        if let RExprKind::Block { stmts, .. } = &block.kind {
+        if let RExprKind::NeverToAny { source } = &block.kind {
+            return self.analyze_block(source.clone(), env);
+        }
+        if let RExprKind::Block { stmts, expr } = &block.kind {
             for stmt in stmts {
                 self.analyze_expr(stmt.clone(), env)?;
+                if env.returned.is_some() {
+                    return Ok(());
+                }
+            }
+            if let Some(expr) = expr {
+                self.analyze_expr(expr.clone(), env)?;
             }
         } else {
             return Err(AnalysisError::Unsupported(
@@ -427,12 +984,39 @@ impl<'tcx> Analyzer<'tcx> {
         env: &mut Env<'tcx>,
     ) -> Result<AnalysisType<'tcx>, AnalysisError> {
         match ty.kind() {
-            TyKind::FnDef(def_id, ..) => {
-                let mut fn_info = self.get_fn_info(def_id);
-                if let Some(fn_thir) = self.get_local_fn(def_id) {
-                    self.analyze_local_fn(fn_thir, args, env)
-                } else {
-                    self.analyze_annotate_fn(fn_info, args, env)
+            TyKind::FnDef(def_id, generic_args)
+                if self.fn_trait_callee(*def_id, generic_args).is_some() =>
+            {
+                self.fn_to_const(ty, args, body, env)?;
+                Ok(AnalysisType::Other)
+            }
+            TyKind::FnPtr(..) => self.analyze_fn_ptr_call(body, env),
+            TyKind::FnDef(def_id, generic_args) => {
+                let (def_id, generic_args, dynamic) = self.resolve_fn(*def_id, generic_args);
+                let mut fn_info = self.get_fn_info(&def_id);
+                match self.get_local_instance(&def_id, generic_args)? {
+                    Some(fn_thir) if self.is_contract(&fn_thir) => {
+                        self.contract_to_const(def_id, fn_thir, args, body, env)?;
+                        Ok(AnalysisType::Other)
+                    }
+                    Some(fn_thir) if self.tcx.is_closure_like(def_id) => {
+                        self.closure_call_to_const(def_id, fn_thir, &args, env)?;
+                        Ok(AnalysisType::Other)
+                    }
+                    Some(fn_thir) if !dynamic => self.analyze_local_fn(fn_thir, args, env),
+                    _ if dynamic => {
+                        self.havoc_call_to_const(body, env)?;
+                        Ok(AnalysisType::Other)
+                    }
+                    _ if self.is_std_diverging_fn(def_id) => {
+                        self.std_diverging_call_to_const(def_id, body, env)?;
+                        Ok(AnalysisType::Other)
+                    }
+                    _ if !def_id.is_local() && fn_info[0] != "verify_modules" => {
+                        self.extern_fn_to_const(def_id, generic_args, &args, body, env)?;
+                        Ok(AnalysisType::Other)
+                    }
+                    _ => self.analyze_annotate_fn(fn_info, args, body, env),
                 }
             }
             _ => return Err(AnalysisError::Unsupported("FnDef is not found".to_string())),
@@ -446,6 +1030,7 @@ impl<'tcx> Analyzer<'tcx> {
         env: &mut Env<'tcx>,
     ) -> Result<AnalysisType<'tcx>, AnalysisError> {
         self.analyze_params(&rthir.params, args, env)?;
+        let depth = env.returns.len();
         if let Some(body) = &rthir.body {
             self.analyze_body((*body).clone(), env)?;
         } else {
@@ -453,6 +1038,8 @@ impl<'tcx> Analyzer<'tcx> {
                 "No RThir body Found".to_string(),
             ));
         }
+        // Statements after the call run wherever the body returned.
+        env.fold_returns(depth)?;
         Ok(AnalysisType::Other)
     }
 
@@ -460,13 +1047,22 @@ impl<'tcx> Analyzer<'tcx> {
         &self,
         fn_info: Vec<String>,
         args: Box<[Rc<RExpr<'tcx>>]>,
+        call: Rc<RExpr<'tcx>>,
         env: &mut Env<'tcx>,
     ) -> Result<AnalysisType<'tcx>, AnalysisError> {
         if fn_info[0] == "verify_modules" {
             match fn_info[1].as_str() {
                 "Vassert" => self.analyze_assert(args, env),
                 "Vassume" => self.analyze_assume(args, env),
+                "Vcover" => self.analyze_cover(args, env),
+                "Vunreachable" => {
+                    self.unreachable_to_const(call, env)?;
+                    Ok(AnalysisType::Other)
+                }
                 "Vinvariant" => self.analyze_invariant(args, env),
+                "Vrequires" | "Vensures" | "Vextern_spec" => Err(AnalysisError::Unsupported(
+                    "Contracts and extern specs cannot be called directly".to_string(),
+                )),
                 _ => unreachable!(),
             }
         } else {
@@ -482,12 +1078,32 @@ impl<'tcx> Analyzer<'tcx> {
         env: &mut Env<'tcx>,
     ) -> Result<String, AnalysisError> {
         match ty.kind() {
-            TyKind::FnDef(def_id, ..) => {
-                let fn_info = self.get_fn_info(def_id);
-                if let Some(fn_thir) = self.get_local_fn(def_id) {
-                    self.local_fn_to_const(fn_thir, args, env)
-                } else {
-                    self.annotate_fn_to_const(fn_info, args, env)
+            TyKind::FnDef(def_id, generic_args)
+                if self.fn_trait_callee(*def_id, generic_args).is_some() =>
+            {
+                let callee_ty = self.fn_trait_callee(*def_id, generic_args).unwrap();
+                self.fn_trait_call_to_const(callee_ty, &args, body, env)
+            }
+            TyKind::FnPtr(..) => self.fn_ptr_call_to_expr(body, env),
+            TyKind::FnDef(def_id, generic_args) => {
+                let (def_id, generic_args, dynamic) = self.resolve_fn(*def_id, generic_args);
+                let fn_info = self.get_fn_info(&def_id);
+                match self.get_local_instance(&def_id, generic_args)? {
+                    Some(fn_thir) if self.is_contract(&fn_thir) => {
+                        self.contract_to_const(def_id, fn_thir, args, body, env)
+                    }
+                    Some(fn_thir) if self.tcx.is_closure_like(def_id) => {
+                        self.closure_call_to_const(def_id, fn_thir, &args, env)
+                    }
+                    Some(fn_thir) if !dynamic => self.local_fn_to_const(fn_thir, args, env),
+                    _ if dynamic => self.havoc_call_to_const(body, env),
+                    _ if self.is_std_diverging_fn(def_id) => {
+                        self.std_diverging_call_to_const(def_id, body, env)
+                    }
+                    _ if !def_id.is_local() && fn_info[0] != "verify_modules" => {
+                        self.extern_fn_to_const(def_id, generic_args, &args, body, env)
+                    }
+                    _ => self.annotate_fn_to_const(fn_info, args, body, env),
                 }
             }
             _ => return Err(AnalysisError::Unsupported("FnDef is not found".to_string())),
@@ -501,27 +1117,59 @@ impl<'tcx> Analyzer<'tcx> {
         env: &mut Env<'tcx>,
     ) -> Result<String, AnalysisError> {
         self.analyze_params(&rthir.params, args, env)?;
-        if let Some(body) = &rthir.body {
-            self.block_to_const(body.clone(), env)
+        self.fn_body_to_const(&rthir, env)
+    }
+
+    /// The value of a call of a local function whose parameters are bound.
+    pub fn fn_body_to_const(
+        &self,
+        rthir: &RThir<'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let depth = env.returns.len();
+        let value = if let Some(body) = &rthir.body {
+            match body.kind {
+                RExprKind::Block { .. } => self.block_to_const(body.clone(), env)?,
+                // Closure bodies need not be blocks.
+                _ => self.expr_to_const(body.clone(), env)?,
+            }
         } else {
             return Err(AnalysisError::Unsupported(
                 "No RThir body Found".to_string(),
             ));
-        }
+        };
+        // The call evaluates to the value returned where the body returned early.
+        Ok(match env.fold_returns(depth)? {
+            Some((cond, ret_value, _)) if cond == "true" => ret_value,
+            Some((cond, ret_value, _)) => format!("(ite {} {} {})", cond, ret_value, value),
+            None => value,
+        })
     }
 
     pub fn annotate_fn_to_const(
         &self,
         fn_info: Vec<String>,
         args: Box<[Rc<RExpr<'tcx>>]>,
+        call: Rc<RExpr<'tcx>>,
         env: &mut Env<'tcx>,
     ) -> Result<String, AnalysisError> {
         if fn_info[0] == "verify_modules" {
             match fn_info[1].as_str() {
+                "Vunreachable" => self.unreachable_to_const(call, env),
+                "Vcover" => {
+                    self.analyze_cover(args, env)?;
+                    self.zst_to_const(self.tcx.types.unit, env)
+                }
                 "Vrand_int" => Err(AnalysisError::RandFunctions),
                 "Vrand_bool" => Err(AnalysisError::RandFunctions),
                 "Vrand_float" => Err(AnalysisError::RandFunctions),
-                _ => unreachable!(),
+                "Vresult" => env.result.clone().ok_or(AnalysisError::Unsupported(
+                    "Vresult is only supported in postconditions".to_string(),
+                )),
+                name => Err(AnalysisError::Unsupported(format!(
+                    "{} is not supported as a value",
+                    name
+                ))),
             }
         } else {
             Err(AnalysisError::Unsupported("Unknown extern function".into()))
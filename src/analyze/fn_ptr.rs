@@ -0,0 +1,222 @@
+use rustc_middle::ty::{GenericArgsRef, Ty, TyKind};
+use rustc_span::def_id::DefId;
+
+use crate::analyze::core::AnalysisType;
+use crate::analyze::*;
+
+/// A function that was turned into a function pointer on the current path.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FnPtrTarget<'tcx> {
+    /// The function pointer type.
+    pub ty: Ty<'tcx>,
+    /// The SMT constant standing for the pointer to this function.
+    pub name: String,
+    pub def_id: DefId,
+    pub args: GenericArgsRef<'tcx>,
+}
+
+/// Function pointers are constants of an uninterpreted sort, one per function reified on the
+/// path. A call through a pointer is a case split over those functions; pointers of unknown
+/// origin fall back to an uninterpreted function.
+impl<'tcx> Analyzer<'tcx> {
+    /// `ReifyFnPointer`/`ClosureFnPointer` coercion of a function item or closure.
+    pub fn reify_to_const(
+        &self,
+        ptr_ty: Ty<'tcx>,
+        source: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let (def_id, args) = match source.ty.kind() {
+            TyKind::FnDef(def_id, args) | TyKind::Closure(def_id, args) => (*def_id, *args),
+            _ => {
+                return Err(AnalysisError::Unsupported(format!(
+                    "Unsupported function pointer source {:?}",
+                    source.ty
+                )))
+            }
+        };
+        let sort = env.ty_to_sort(ptr_ty)?;
+        let name = format!(
+            "fn_{}",
+            self.tcx
+                .def_path_str_with_args(def_id, args)
+                .replace(|c: char| !c.is_alphanumeric(), "_")
+        );
+        if env.fn_ptrs.iter().any(|target| target.name == name) {
+            return Ok(name);
+        }
+        let mut datatypes = env.datatypes.borrow_mut();
+        if !datatypes.iter().any(|(n, _)| *n == name) {
+            datatypes.push((name.clone(), format!("(declare-const {} {})", name, sort)));
+        }
+        // Distinct functions have distinct pointers.
+        for target in env.fn_ptrs.iter().filter(|target| target.ty == ptr_ty) {
+            let distinct = format!("distinct_{}_{}", target.name, name);
+            if !datatypes.iter().any(|(n, _)| *n == distinct) {
+                let decl = format!("(assert (distinct {} {}))", target.name, name);
+                datatypes.push((distinct, decl));
+            }
+        }
+        drop(datatypes);
+        env.fn_ptrs.push(FnPtrTarget {
+            ty: ptr_ty,
+            name: name.clone(),
+            def_id,
+            args,
+        });
+        Ok(name)
+    }
+
+    /// The callee type of a call through the `Fn*` traits on a function item or pointer.
+    pub fn fn_trait_callee(&self, def_id: DefId, args: GenericArgsRef<'tcx>) -> Option<Ty<'tcx>> {
+        let trait_id = self.tcx.trait_of_item(def_id)?;
+        self.tcx.fn_trait_kind_from_def_id(trait_id)?;
+        let callee_ty = args.type_at(0).peel_refs();
+        matches!(callee_ty.kind(), TyKind::FnDef(..) | TyKind::FnPtr(..)).then_some(callee_ty)
+    }
+
+    /// A call `f(args)` through the `Fn*` traits, with `f` a function item or pointer.
+    pub fn fn_trait_call_to_const(
+        &self,
+        callee_ty: Ty<'tcx>,
+        args: &[Rc<RExpr<'tcx>>],
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let call_args = Analyzer::untuple_args(args[1].clone())?;
+        if let TyKind::FnPtr(..) = callee_ty.kind() {
+            let mut callee = args[0].clone();
+            while let RExprKind::Borrow { arg, .. } = &callee.kind {
+                callee = arg.clone();
+            }
+            let ptr = self.expr_to_const(callee, env)?;
+            self.fn_ptr_call_to_const(callee_ty, &ptr, &call_args, call, env)
+        } else {
+            self.fn_to_const(callee_ty, call_args.into_boxed_slice(), call, env)
+        }
+    }
+
+    pub fn analyze_fn_ptr_call(
+        &self,
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<AnalysisType<'tcx>, AnalysisError> {
+        self.fn_ptr_call_to_expr(call, env)?;
+        Ok(AnalysisType::Other)
+    }
+
+    /// A call whose callee expression is a function pointer.
+    pub fn fn_ptr_call_to_expr(
+        &self,
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let RExprKind::Call { fun, args, .. } = &call.kind else {
+            return Err(AnalysisError::Unsupported(
+                "Only calls can be made through function pointers".to_string(),
+            ));
+        };
+        let ptr = self.expr_to_const(fun.clone(), env)?;
+        self.fn_ptr_call_to_const(fun.ty, &ptr, args, call.clone(), env)
+    }
+
+    pub fn fn_ptr_call_to_const(
+        &self,
+        ptr_ty: Ty<'tcx>,
+        ptr: &str,
+        args: &[Rc<RExpr<'tcx>>],
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let targets = env
+            .fn_ptrs
+            .iter()
+            .filter(|target| target.ty == ptr_ty)
+            .cloned()
+            .collect::<Vec<FnPtrTarget<'tcx>>>();
+        if targets.is_empty() {
+            println!(
+                "Warning: no function is known to flow into the pointer called at {:?}; its result is uninterpreted",
+                call.span
+            );
+        }
+        self.fn_ptr_cases_to_const(ptr_ty, ptr, &targets, args, call, env)
+    }
+
+    fn fn_ptr_cases_to_const(
+        &self,
+        ptr_ty: Ty<'tcx>,
+        ptr: &str,
+        targets: &[FnPtrTarget<'tcx>],
+        args: &[Rc<RExpr<'tcx>>],
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let Some((target, rest)) = targets.split_first() else {
+            return self.uninterpreted_call_to_const(ptr_ty, ptr, args, call, env);
+        };
+        let cond = format!("(= {} {})", ptr, target.name);
+
+        let mut then_env = env.new_env_from_str("then".to_string(), call.span)?;
+        then_env.add_smt_command(cond.clone(), call.clone());
+        let then_str = self.fn_ptr_target_to_const(target, args, call.clone(), &mut then_env)?;
+
+        let mut else_env = env.new_env_from_str("else".to_string(), call.span)?;
+        else_env.add_smt_command(format!("(not {})", cond), call.clone());
+        let else_str =
+            self.fn_ptr_cases_to_const(ptr_ty, ptr, rest, args, call.clone(), &mut else_env)?;
+
+        env.merge_ite_env(&cond, then_env, Some(else_env))?;
+        Ok(format!("(ite {} {} {})", cond, then_str, else_str))
+    }
+
+    fn fn_ptr_target_to_const(
+        &self,
+        target: &FnPtrTarget<'tcx>,
+        args: &[Rc<RExpr<'tcx>>],
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        if self.tcx.is_closure_like(target.def_id) {
+            // Only closures without captures coerce to function pointers; the closure
+            // itself is never read, so any expression can stand in for it.
+            let rthir = self
+                .get_local_instance(&target.def_id, target.args)?
+                .ok_or(AnalysisError::Unsupported("No RThir body Found".to_string()))?;
+            let mut call_args = vec![call.clone()];
+            call_args.extend(args.iter().cloned());
+            return self.local_fn_to_const(rthir, call_args.into_boxed_slice(), env);
+        }
+        let fn_ty = Ty::new_fn_def(self.tcx, target.def_id, target.args);
+        self.fn_to_const(fn_ty, args.into(), call, env)
+    }
+
+    fn uninterpreted_call_to_const(
+        &self,
+        ptr_ty: Ty<'tcx>,
+        ptr: &str,
+        args: &[Rc<RExpr<'tcx>>],
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let ptr_sort = env.ty_to_sort(ptr_ty)?;
+        let mut sorts = vec![ptr_sort.clone()];
+        let mut values = vec![ptr.to_string()];
+        for arg in args.iter() {
+            sorts.push(env.ty_to_sort(arg.ty)?);
+            values.push(self.expr_to_const(arg.clone(), env)?);
+        }
+        let name = format!("call_{}", ptr_sort);
+        let decl = format!(
+            "(declare-fun {} ({}) {})",
+            name,
+            sorts.join(" "),
+            env.ty_to_sort(call.ty)?
+        );
+        let mut datatypes = env.datatypes.borrow_mut();
+        if !datatypes.iter().any(|(n, _)| *n == name) {
+            datatypes.push((name.clone(), decl));
+        }
+        Ok(format!("({} {})", name, values.join(" ")))
+    }
+}
@@ -0,0 +1,209 @@
+use rustc_hir::def::DefKind;
+use rustc_middle::ty::{Mutability, ParamEnv, Ty, TyKind};
+use rustc_span::def_id::DefId;
+
+use crate::analyze::mir::ExternCall;
+use crate::analyze::*;
+
+/// Mutable global state: `static mut`s, statics with interior mutability such as atomics,
+/// and the slots of `thread_local!` keys. Calls are inlined, so their values are threaded
+/// through the analysis like local variables. The program is assumed to be
+/// single-threaded: atomic operations take effect immediately.
+impl<'tcx> Analyzer<'tcx> {
+    /// The global state of the crate, with the type of its value: that of the static, or
+    /// `T` for a `LocalKey<T>`.
+    fn global_state(&self) -> Vec<(DefId, Ty<'tcx>)> {
+        let mut state: Vec<(DefId, Ty<'tcx>)> = self
+            .fn_map
+            .keys()
+            .filter_map(|id| {
+                let def_id = id.to_def_id();
+                let ty = self.tcx.type_of(def_id).instantiate_identity();
+                match self.tcx.def_kind(def_id) {
+                    DefKind::Static {
+                        mutability: Mutability::Mut,
+                        ..
+                    } => Some((def_id, ty)),
+                    // `#[thread_local]` statics belong to the expansion of `thread_local!`.
+                    DefKind::Static { .. }
+                        if !self.tcx.is_thread_local_static(def_id)
+                            && !ty.is_freeze(self.tcx, ParamEnv::reveal_all()) =>
+                    {
+                        Some((def_id, ty))
+                    }
+                    DefKind::Const => self.local_key_value_ty(ty).map(|ty| (def_id, ty)),
+                    _ => None,
+                }
+            })
+            .collect();
+        state.sort_by_key(|(def_id, _)| def_id.index);
+        state
+    }
+
+    fn local_key_value_ty(&self, ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+        match ty.kind() {
+            TyKind::Adt(adt_def, args)
+                if self.tcx.def_path_str(adt_def.did()) == "std::thread::LocalKey" =>
+            {
+                Some(args.type_at(0))
+            }
+            _ => None,
+        }
+    }
+
+    /// Sets up the global state: at the entry of `main` it holds the values of the
+    /// initializers, while a function checked on its own may find it in any state.
+    pub fn init_global_state(
+        &self,
+        initialized: bool,
+        expr: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<(), AnalysisError> {
+        for (def_id, ty) in self.global_state() {
+            let value = match initialized.then(|| self.initializer_to_const(def_id, env)) {
+                Some(Ok(value)) => value,
+                Some(Err(AnalysisError::Unsupported(reason))) => {
+                    println!(
+                        "Warning: the initial value of {} is not analyzed ({}); it is unconstrained",
+                        self.tcx.def_path_str(def_id),
+                        reason
+                    );
+                    let name = format!("static_{}", self.global_name(def_id));
+                    self.havoc_global_value(name, ty, expr.clone(), env)
+                }
+                Some(Err(err)) => return Err(err),
+                None => {
+                    let name = format!("static_{}", self.global_name(def_id));
+                    self.havoc_global_value(name, ty, expr.clone(), env)
+                }
+            };
+            env.statics.insert(def_id, (ty, value));
+        }
+        Ok(())
+    }
+
+    /// The initializer of a static, or the `__init` function generated for a
+    /// `thread_local!` key.
+    fn initializer_to_const(
+        &self,
+        def_id: DefId,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let init_id = match self.tcx.def_kind(def_id) {
+            DefKind::Const => self
+                .fn_map
+                .keys()
+                .map(|id| id.to_def_id())
+                .find(|id| {
+                    self.tcx.opt_parent(*id) == Some(def_id)
+                        && self
+                            .tcx
+                            .opt_item_name(*id)
+                            .is_some_and(|name| name.as_str() == "__init")
+                })
+                .ok_or(AnalysisError::Unsupported(
+                    "const-initialized thread-locals are not supported".to_string(),
+                ))?,
+            _ => def_id,
+        };
+        match self
+            .get_local_fn(&init_id)
+            .and_then(|rthir| rthir.body.clone())
+        {
+            Some(body) => self.expr_to_const(body, env),
+            None => Err(AnalysisError::Unsupported(
+                "No RThir body Found".to_string(),
+            )),
+        }
+    }
+
+    /// Forgets the global state, e.g. across a call of which only the contract is known.
+    pub fn havoc_global_state(&self, expr: Rc<RExpr<'tcx>>, env: &mut Env<'tcx>) {
+        let mut state: Vec<DefId> = env.statics.keys().copied().collect();
+        state.sort_by_key(|def_id| def_id.index);
+        for def_id in state {
+            self.havoc_global(def_id, "havoc", expr.clone(), env);
+        }
+    }
+
+    pub fn havoc_global(
+        &self,
+        def_id: DefId,
+        prefix: &str,
+        expr: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) {
+        let ty = env.statics[&def_id].0;
+        let name = format!(
+            "{}_{}",
+            env.fresh_name(prefix.to_string(), expr.span),
+            self.global_name(def_id)
+        );
+        let value = self.havoc_global_value(name, ty, expr, env);
+        env.statics.insert(def_id, (ty, value));
+    }
+
+    fn havoc_global_value(
+        &self,
+        name: String,
+        ty: Ty<'tcx>,
+        expr: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> String {
+        env.add_random_var(ty, name.clone());
+        if let Some(range) = self.int_range_to_const(&name, ty) {
+            env.add_smt_command(range, expr);
+        }
+        name
+    }
+
+    fn global_name(&self, def_id: DefId) -> String {
+        Analyzer::get_name_from_span(self.tcx.def_span(def_id))
+    }
+
+    /// `LocalKey::with(f)`: `f` is called with a reference to the slot of the key.
+    pub fn local_key_with_to_const(
+        &self,
+        call: &ExternCall<'_, 'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let (Some(exprs), TyKind::Closure(closure_id, closure_args)) =
+            (call.exprs, call.tys[1].kind())
+        else {
+            return Err(AnalysisError::Unsupported(
+                "LocalKey::with is only modelled for closures of the analyzed crate".to_string(),
+            ));
+        };
+        let key = Analyzer::local_key(&exprs[0])
+            .filter(|key| env.statics.contains_key(key))
+            .ok_or(AnalysisError::Unsupported(
+                "LocalKey::with is only modelled for keys of the analyzed crate".to_string(),
+            ))?;
+        let rthir = self.get_local_instance(closure_id, closure_args)?.ok_or(
+            AnalysisError::Unsupported("No RThir body Found".to_string()),
+        )?;
+        let span = call.call.span;
+        let slot_ty = Ty::new_imm_ref(self.tcx, self.tcx.lifetimes.re_erased, env.statics[&key].0);
+        let slot = Rc::new(RExpr::new(
+            RExprKind::StaticRef { def_id: key },
+            slot_ty,
+            span,
+        ));
+        let args = Rc::new(RExpr::new(
+            RExprKind::Tuple {
+                fields: Box::new([slot]),
+            },
+            Ty::new_tup(self.tcx, &[slot_ty]),
+            span,
+        ));
+        self.closure_call_to_const(*closure_id, rthir, &[exprs[1].clone(), args], env)
+    }
+
+    fn local_key(expr: &RExpr<'tcx>) -> Option<DefId> {
+        match &expr.kind {
+            RExprKind::Borrow { arg, .. } | RExprKind::Deref { arg } => Analyzer::local_key(arg),
+            RExprKind::NamedConst { def_id, .. } => Some(*def_id),
+            _ => None,
+        }
+    }
+}
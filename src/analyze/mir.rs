@@ -0,0 +1,694 @@
+use rustc_middle::mir::{
+    AggregateKind, BasicBlock, BinOp, Body, BorrowKind, CastKind, Operand, Place as MirPlace,
+    ProjectionElem, Rvalue, StatementKind, TerminatorKind, START_BLOCK,
+};
+use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::{
+    EarlyBinder, GenericArgsRef, Instance, InstanceKind, ParamEnv, ScalarInt, Ty, TyKind,
+    TypeFoldable,
+};
+use rustc_span::def_id::DefId;
+use rustc_target::abi::{Size, VariantIdx};
+
+use crate::analyze::encoding::Encoding;
+use crate::analyze::*;
+
+/// Basic blocks that may be visited while evaluating one call; MIR loops are not unrolled.
+const MIR_BLOCK_LIMIT: usize = 256;
+/// Nesting of calls between functions of other crates.
+const MIR_CALL_DEPTH: usize = 8;
+
+/// A function of another crate being evaluated on its MIR body.
+struct MirFrame<'tcx> {
+    body: &'tcx Body<'tcx>,
+    instance: Instance<'tcx>,
+    depth: usize,
+    /// The call of the analyzed crate that led to this function.
+    call: Rc<RExpr<'tcx>>,
+}
+
+impl<'tcx> MirFrame<'tcx> {
+    fn mono<T: TypeFoldable<TyCtxt<'tcx>>>(&self, tcx: TyCtxt<'tcx>, value: T) -> T {
+        self.instance.instantiate_mir_and_normalize_erasing_regions(
+            tcx,
+            ParamEnv::reveal_all(),
+            EarlyBinder::bind(value),
+        )
+    }
+}
+
+/// A call of a function of another crate.
+pub struct ExternCall<'a, 'tcx> {
+    pub def_id: DefId,
+    pub generic_args: GenericArgsRef<'tcx>,
+    pub values: Vec<String>,
+    pub tys: Vec<Ty<'tcx>>,
+    pub ret_ty: Ty<'tcx>,
+    /// The argument expressions, when the call is made by the analyzed crate.
+    pub exprs: Option<&'a [Rc<RExpr<'tcx>>]>,
+    /// The call of the analyzed crate being evaluated.
+    pub call: Rc<RExpr<'tcx>>,
+    /// The branch conditions of the MIR bodies the call is made under; empty for calls of
+    /// the analyzed crate.
+    pub conds: Vec<String>,
+}
+
+/// Functions of other crates have no THIR. A call is replaced by the contract of a local
+/// extern spec if there is one, or else encoded with a built-in model. Otherwise their MIR
+/// is used, which is encoded in the crate metadata when they are generic, `#[inline]`,
+/// `const`, or the crate is built with `-Zalways-encode-mir`. Loop-free bodies over
+/// scalars, structs and enums are evaluated symbolically; anything else is abstracted by
+/// an unconstrained result.
+impl<'tcx> Analyzer<'tcx> {
+    pub fn extern_fn_to_const(
+        &self,
+        def_id: DefId,
+        generic_args: GenericArgsRef<'tcx>,
+        args: &[Rc<RExpr<'tcx>>],
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        if let Some(spec) = self.find_extern_spec(&def_id) {
+            return self.contract_to_const(def_id, spec, args.into(), call, env);
+        }
+        let mut values = Vec::new();
+        for arg in args.iter() {
+            values.push(self.expr_to_const(arg.clone(), env)?);
+        }
+        let extern_call = ExternCall {
+            def_id,
+            generic_args,
+            values,
+            tys: args.iter().map(|arg| arg.ty).collect(),
+            ret_ty: call.ty,
+            exprs: Some(args),
+            call: call.clone(),
+            conds: Vec::new(),
+        };
+        let result = if args.iter().any(|arg| Analyzer::is_mut_ref(arg.ty)) {
+            Err(AnalysisError::Unsupported(
+                "mutable reference arguments".to_string(),
+            ))
+        } else if args.iter().any(|arg| self.is_cell_ref(arg.ty)) {
+            // Writes through shared references are only known from the models.
+            match self.find_model(def_id) {
+                Some(model) => self.model_to_const(model, &extern_call, env).map(Some),
+                None => Err(AnalysisError::Unsupported(
+                    "interior mutability".to_string(),
+                )),
+            }
+        } else {
+            self.mir_call_to_const(&extern_call, 0, env)
+        };
+        let reason = match result {
+            Err(AnalysisError::Unsupported(reason)) => reason,
+            Ok(Some(value)) => return Ok(value),
+            // The callee never returns.
+            Ok(None) => {
+                env.diverge(call.clone());
+                return self.never_to_const(call, env);
+            }
+            Err(err) => return Err(err),
+        };
+        println!(
+            "Warning: {} called at {:?} is not analyzed ({}); its result is unconstrained",
+            self.tcx.def_path_str_with_args(def_id, generic_args),
+            call.span,
+            reason
+        );
+        // The panics of the callee cannot be ruled out, so the call must be unreachable.
+        if let Ok(Some(instance)) =
+            Instance::resolve(self.tcx, ParamEnv::reveal_all(), def_id, generic_args)
+        {
+            if self.mir_may_panic(instance, 0, &mut HashSet::new()) {
+                println!(
+                    "Warning: {} may panic; the call at {:?} is only verified if it is unreachable",
+                    self.tcx.def_path_str_with_args(def_id, generic_args),
+                    call.span
+                );
+                self.verify_no_panic(&[], "false".to_string(), call.span, env)?;
+            }
+        }
+        for arg in args
+            .iter()
+            .filter(|arg| Analyzer::is_mut_ref(arg.ty) || self.is_cell_ref(arg.ty))
+        {
+            if let TyKind::Ref(_, inner_ty, _) = arg.ty.kind() {
+                let name = env.fresh_name("havoc".to_string(), arg.span);
+                env.add_random_var(*inner_ty, name.clone());
+                let place = self.ref_target(arg.clone(), env)?;
+                env.write_place(&place, name)?;
+            }
+        }
+        // A closure of the analyzed crate may be called by the callee.
+        for arg in args.iter() {
+            self.havoc_closure_captures(arg.ty.peel_refs(), call.clone(), env)?;
+        }
+        self.havoc_global_state(call.clone(), env);
+        Ok(self.havoc_result(call, env))
+    }
+
+    /// Evaluates a call with its built-in model if there is one, or else on its MIR body.
+    /// The value is `None` if the callee never returns.
+    fn mir_call_to_const(
+        &self,
+        call: &ExternCall<'_, 'tcx>,
+        depth: usize,
+        env: &mut Env<'tcx>,
+    ) -> Result<Option<String>, AnalysisError> {
+        if let Some(model) = self.find_model(call.def_id) {
+            match self.model_to_const(model, call, env) {
+                Err(AnalysisError::Unsupported(_)) => {}
+                result => return result.map(Some),
+            }
+        }
+        if depth > MIR_CALL_DEPTH {
+            return Err(AnalysisError::Unsupported(
+                "calls nested too deeply".to_string(),
+            ));
+        }
+        let instance = match Instance::resolve(
+            self.tcx,
+            ParamEnv::reveal_all(),
+            call.def_id,
+            call.generic_args,
+        ) {
+            Ok(Some(instance)) => instance,
+            _ => {
+                return Err(AnalysisError::Unsupported(
+                    "the callee cannot be resolved".to_string(),
+                ))
+            }
+        };
+        let body = match instance.def {
+            InstanceKind::Item(id) if id.is_local() => {
+                return self
+                    .mir_local_call_to_const(id, instance.args, call, env)
+                    .map(Some)
+            }
+            _ => self
+                .extern_mir(instance)
+                .ok_or(AnalysisError::Unsupported("no MIR available".to_string()))?,
+        };
+        let mut locals = vec![None; body.local_decls.len()];
+        for (idx, value) in call.values.iter().enumerate() {
+            locals[idx + 1] = Some(value.clone());
+        }
+        let frame = MirFrame {
+            body,
+            instance,
+            depth,
+            call: call.call.clone(),
+        };
+        let mut steps = 0;
+        self.mir_block_to_const(
+            &frame,
+            START_BLOCK,
+            locals,
+            call.conds.clone(),
+            &mut steps,
+            env,
+        )
+    }
+
+    /// The MIR body of a function of another crate, if it is available.
+    fn extern_mir(&self, instance: Instance<'tcx>) -> Option<&'tcx Body<'tcx>> {
+        match instance.def {
+            // Building the MIR of a local function would steal the THIR we analyze.
+            InstanceKind::Item(id) if id.is_local() => None,
+            InstanceKind::Item(id) if self.tcx.is_mir_available(id) => {
+                Some(self.tcx.optimized_mir(id))
+            }
+            InstanceKind::Item(id) if self.tcx.is_ctfe_mir_available(id) => {
+                Some(self.tcx.mir_for_ctfe(id))
+            }
+            InstanceKind::Item(_) | InstanceKind::Intrinsic(_) | InstanceKind::Virtual(..) => None,
+            // Compiler-generated shims, e.g. calls of function items through `Fn*` traits.
+            shim => Some(self.tcx.instance_mir(shim)),
+        }
+    }
+
+    /// Whether a function of another crate may panic: its MIR, or that of a function it
+    /// calls, asserts or calls a panicking function. Functions without MIR, calls nested
+    /// too deeply and calls back into the analyzed crate are assumed not to.
+    fn mir_may_panic(
+        &self,
+        instance: Instance<'tcx>,
+        depth: usize,
+        visited: &mut HashSet<Instance<'tcx>>,
+    ) -> bool {
+        if depth > MIR_CALL_DEPTH || !visited.insert(instance) {
+            return false;
+        }
+        let Some(body) = self.extern_mir(instance) else {
+            return false;
+        };
+        body.basic_blocks
+            .iter()
+            .any(|data| match &data.terminator().kind {
+                TerminatorKind::Assert { .. } => true,
+                TerminatorKind::Call { func, .. } => {
+                    let callee_ty = instance.instantiate_mir_and_normalize_erasing_regions(
+                        self.tcx,
+                        ParamEnv::reveal_all(),
+                        EarlyBinder::bind(func.ty(body, self.tcx)),
+                    );
+                    let TyKind::FnDef(callee, callee_args) = callee_ty.kind() else {
+                        return false;
+                    };
+                    if self.is_std_diverging_fn(*callee) {
+                        return self.is_panic_fn(*callee);
+                    }
+                    match Instance::resolve(self.tcx, ParamEnv::reveal_all(), *callee, callee_args)
+                    {
+                        Ok(Some(callee)) => self.mir_may_panic(callee, depth + 1, visited),
+                        _ => false,
+                    }
+                }
+                _ => false,
+            })
+    }
+
+    /// A call back into the analyzed crate, e.g. of a `From` impl converting the error of
+    /// `?`. The local function is inlined where the MIR branch conditions hold.
+    fn mir_local_call_to_const(
+        &self,
+        def_id: DefId,
+        generic_args: GenericArgsRef<'tcx>,
+        call: &ExternCall<'_, 'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let rthir = match self.get_local_instance(&def_id, generic_args)? {
+            Some(rthir)
+                if !self.is_contract(&rthir)
+                    && !self.tcx.is_closure_like(def_id)
+                    && !call.tys.iter().any(|ty| Analyzer::is_mut_ref(*ty)) =>
+            {
+                rthir
+            }
+            _ => {
+                return Err(AnalysisError::Unsupported(
+                    "calls back into the analyzed crate".to_string(),
+                ))
+            }
+        };
+        let mut call_env = env.new_env_from_str("callback".to_string(), call.call.span)?;
+        for cond in call.conds.iter() {
+            call_env.add_smt_command(cond.clone(), call.call.clone());
+        }
+        for (param, value) in rthir.params.iter().zip(call.values.iter()) {
+            if let Some(pat) = &param.pat {
+                self.bind_pattern(pat.clone(), value.clone(), &mut call_env)?;
+            }
+        }
+        let value = self.fn_body_to_const(&rthir, &mut call_env)?;
+        let cond = match call.conds.len() {
+            0 => "true".to_string(),
+            _ => format!("(and true {})", call.conds.join(" ")),
+        };
+        env.merge_ite_env(&cond, call_env, None)?;
+        Ok(value)
+    }
+
+    /// The value returned by `frame` when execution reaches `block` under the branch
+    /// conditions `conds`, or `None` if it never returns. Panics on the way are verified
+    /// to be unreachable.
+    fn mir_block_to_const(
+        &self,
+        frame: &MirFrame<'tcx>,
+        mut block: BasicBlock,
+        mut locals: Vec<Option<String>>,
+        conds: Vec<String>,
+        steps: &mut usize,
+        env: &mut Env<'tcx>,
+    ) -> Result<Option<String>, AnalysisError> {
+        loop {
+            *steps += 1;
+            if *steps > MIR_BLOCK_LIMIT {
+                return Err(AnalysisError::Unsupported(
+                    "the body is too large or loops".to_string(),
+                ));
+            }
+            let data = &frame.body.basic_blocks[block];
+            for stmt in data.statements.iter() {
+                match &stmt.kind {
+                    StatementKind::Assign(assign) => {
+                        let (place, rvalue) = &**assign;
+                        let value = self.mir_rvalue_to_const(frame, rvalue, &locals, env)?;
+                        Analyzer::mir_write(place, value, &mut locals)?;
+                    }
+                    StatementKind::StorageLive(_)
+                    | StatementKind::StorageDead(_)
+                    | StatementKind::FakeRead(_)
+                    | StatementKind::Retag(..)
+                    | StatementKind::PlaceMention(_)
+                    | StatementKind::AscribeUserType(..)
+                    | StatementKind::Coverage(_)
+                    | StatementKind::ConstEvalCounter
+                    | StatementKind::Nop => {}
+                    kind => {
+                        return Err(AnalysisError::Unsupported(format!(
+                            "unsupported statement {:?}",
+                            kind
+                        )))
+                    }
+                }
+            }
+            match &data.terminator().kind {
+                TerminatorKind::Goto { target } => block = *target,
+                TerminatorKind::Assert {
+                    cond,
+                    expected,
+                    target,
+                    ..
+                } => {
+                    let value = self.mir_operand_to_const(frame, cond, &locals, env)?;
+                    let value = if *expected {
+                        value
+                    } else {
+                        format!("(not {})", value)
+                    };
+                    self.verify_no_panic(&conds, value, frame.call.span, env)?;
+                    block = *target;
+                }
+                TerminatorKind::Drop { target, .. } => block = *target,
+                TerminatorKind::Return => {
+                    return match &locals[0] {
+                        Some(value) => Ok(Some(value.clone())),
+                        None => self
+                            .zst_to_const(frame.mono(self.tcx, frame.body.return_ty()), env)
+                            .map(Some),
+                    };
+                }
+                TerminatorKind::Unreachable => return Ok(None),
+                TerminatorKind::SwitchInt { discr, targets } => {
+                    let value = self.mir_operand_to_const(frame, discr, &locals, env)?;
+                    let ty = frame.mono(self.tcx, discr.ty(frame.body, self.tcx));
+                    // Unreachable targets, e.g. the `otherwise` of a match on every
+                    // variant, are left out.
+                    let mut branches = Vec::new();
+                    let mut others = Vec::new();
+                    for (bits, target) in targets.iter() {
+                        let cond = match ty.kind() {
+                            TyKind::Bool if bits == 0 => format!("(not {})", value),
+                            TyKind::Bool => value.clone(),
+                            _ => format!("(= {} {})", value, self.mir_bits_to_const(bits, ty)?),
+                        };
+                        others.push(format!("(not {})", cond));
+                        branches.push((cond, target));
+                    }
+                    branches.push((
+                        format!("(and true {})", others.join(" ")),
+                        targets.otherwise(),
+                    ));
+                    branches.retain(|(_, target)| !Analyzer::is_unreachable_block(frame, *target));
+                    let mut results = Vec::new();
+                    for (cond, target) in branches {
+                        let mut branch_conds = conds.clone();
+                        branch_conds.push(cond.clone());
+                        let value = self.mir_block_to_const(
+                            frame,
+                            target,
+                            locals.clone(),
+                            branch_conds,
+                            steps,
+                            env,
+                        )?;
+                        // Branches that never return are left out as well.
+                        if let Some(value) = value {
+                            results.push((cond, value));
+                        }
+                    }
+                    let Some((_, mut result)) = results.pop() else {
+                        return Ok(None);
+                    };
+                    for (cond, value) in results.into_iter().rev() {
+                        result = format!("(ite {} {} {})", cond, value, result);
+                    }
+                    return Ok(Some(result));
+                }
+                TerminatorKind::Call {
+                    func,
+                    args,
+                    destination,
+                    target,
+                    ..
+                } => {
+                    let callee_ty = frame.mono(self.tcx, func.ty(frame.body, self.tcx));
+                    let TyKind::FnDef(callee, callee_args) = callee_ty.kind() else {
+                        return Err(AnalysisError::Unsupported("indirect call".to_string()));
+                    };
+                    if self.is_std_diverging_fn(*callee) {
+                        if self.is_panic_fn(*callee) {
+                            let span = frame.call.span;
+                            self.verify_no_panic(&conds, "false".to_string(), span, env)?;
+                        }
+                        return Ok(None);
+                    }
+                    let mut values = Vec::new();
+                    let mut tys = Vec::new();
+                    for arg in args.iter() {
+                        values.push(self.mir_operand_to_const(frame, &arg.node, &locals, env)?);
+                        tys.push(frame.mono(self.tcx, arg.node.ty(frame.body, self.tcx)));
+                    }
+                    let extern_call = ExternCall {
+                        def_id: *callee,
+                        generic_args: callee_args,
+                        values,
+                        tys,
+                        ret_ty: frame.mono(self.tcx, destination.ty(frame.body, self.tcx).ty),
+                        exprs: None,
+                        call: frame.call.clone(),
+                        conds: conds.clone(),
+                    };
+                    let value = self.mir_call_to_const(&extern_call, frame.depth + 1, env)?;
+                    let (Some(value), Some(target)) = (value, target) else {
+                        return Ok(None);
+                    };
+                    Analyzer::mir_write(destination, value, &mut locals)?;
+                    block = *target;
+                }
+                kind => {
+                    return Err(AnalysisError::Unsupported(format!(
+                        "unsupported terminator {:?}",
+                        kind
+                    )))
+                }
+            }
+        }
+    }
+
+    fn is_unreachable_block(frame: &MirFrame<'tcx>, block: BasicBlock) -> bool {
+        matches!(
+            frame.body.basic_blocks[block].terminator().kind,
+            TerminatorKind::Unreachable
+        )
+    }
+
+    fn mir_write(
+        place: &MirPlace<'tcx>,
+        value: String,
+        locals: &mut [Option<String>],
+    ) -> Result<(), AnalysisError> {
+        if !place.projection.is_empty() {
+            return Err(AnalysisError::Unsupported(
+                "writes through projections".to_string(),
+            ));
+        }
+        locals[place.local.as_usize()] = Some(value);
+        Ok(())
+    }
+
+    fn mir_read(
+        &self,
+        frame: &MirFrame<'tcx>,
+        place: &MirPlace<'tcx>,
+        locals: &[Option<String>],
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let mut value =
+            locals[place.local.as_usize()]
+                .clone()
+                .ok_or(AnalysisError::Unsupported(
+                    "read of an uninitialized local".to_string(),
+                ))?;
+        let mut ty = frame.mono(self.tcx, frame.body.local_decls[place.local].ty);
+        let mut variant = VariantIdx::from_u32(0);
+        for elem in place.projection.iter() {
+            match (elem, ty.kind()) {
+                // Shared references are encoded as the value they point to.
+                (ProjectionElem::Deref, TyKind::Ref(_, inner_ty, _))
+                    if !Analyzer::is_mut_ref(ty) =>
+                {
+                    ty = *inner_ty;
+                }
+                (ProjectionElem::Downcast(_, variant_idx), TyKind::Adt(..)) => {
+                    variant = variant_idx;
+                }
+                (ProjectionElem::Field(idx, field_ty), _) if Analyzer::is_mir_datatype(ty) => {
+                    let datatype = env.ty_to_sort(ty)?;
+                    value = format!(
+                        "({} {})",
+                        env.variant_selector_name(&datatype, ty, variant, idx.as_usize()),
+                        value
+                    );
+                    ty = frame.mono(self.tcx, field_ty);
+                    variant = VariantIdx::from_u32(0);
+                }
+                _ => {
+                    return Err(AnalysisError::Unsupported(format!(
+                        "unsupported projection {:?}",
+                        elem
+                    )))
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    fn mir_operand_to_const(
+        &self,
+        frame: &MirFrame<'tcx>,
+        operand: &Operand<'tcx>,
+        locals: &[Option<String>],
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => self.mir_read(frame, place, locals, env),
+            Operand::Constant(constant) => {
+                let ty = frame.mono(self.tcx, constant.ty());
+                let layout = self.tcx.layout_of(ParamEnv::reveal_all().and(ty));
+                if layout.is_ok_and(|layout| layout.is_zst()) {
+                    return self.zst_to_const(ty, env);
+                }
+                let lit = frame
+                    .mono(self.tcx, constant.const_)
+                    .try_eval_scalar_int(self.tcx, ParamEnv::reveal_all())
+                    .ok_or(AnalysisError::Unsupported(format!(
+                        "unsupported constant {:?}",
+                        constant
+                    )))?;
+                self.scalar_int_to_const(lit, ty)
+            }
+        }
+    }
+
+    fn mir_rvalue_to_const(
+        &self,
+        frame: &MirFrame<'tcx>,
+        rvalue: &Rvalue<'tcx>,
+        locals: &[Option<String>],
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        match rvalue {
+            Rvalue::Use(operand) => self.mir_operand_to_const(frame, operand, locals, env),
+            Rvalue::Ref(_, BorrowKind::Shared, place) => self.mir_read(frame, place, locals, env),
+            Rvalue::BinaryOp(op, operands) => {
+                let (lhs, rhs) = &**operands;
+                let op = match op {
+                    BinOp::AddUnchecked => BinOp::Add,
+                    BinOp::SubUnchecked => BinOp::Sub,
+                    BinOp::MulUnchecked => BinOp::Mul,
+                    BinOp::ShlUnchecked => BinOp::Shl,
+                    BinOp::ShrUnchecked => BinOp::Shr,
+                    op => *op,
+                };
+                let lhs_ty = frame.mono(self.tcx, lhs.ty(frame.body, self.tcx));
+                let rhs_ty = frame.mono(self.tcx, rhs.ty(frame.body, self.tcx));
+                let lhs_str = self.mir_operand_to_const(frame, lhs, locals, env)?;
+                let mut rhs_str = self.mir_operand_to_const(frame, rhs, locals, env)?;
+                if matches!(op, BinOp::Shl | BinOp::Shr) && lhs_ty != rhs_ty {
+                    rhs_str = self.cast_to_const(&rhs_str, rhs_ty, lhs_ty)?;
+                }
+                self.binop_to_const(op, &lhs_str, &rhs_str, lhs_ty)
+            }
+            Rvalue::UnaryOp(op, operand) => {
+                let ty = frame.mono(self.tcx, operand.ty(frame.body, self.tcx));
+                let value = self.mir_operand_to_const(frame, operand, locals, env)?;
+                self.unop_to_const(*op, &value, ty)
+            }
+            Rvalue::Cast(
+                CastKind::IntToInt
+                | CastKind::IntToFloat
+                | CastKind::FloatToInt
+                | CastKind::FloatToFloat,
+                operand,
+                ty,
+            ) => {
+                let from = frame.mono(self.tcx, operand.ty(frame.body, self.tcx));
+                let value = self.mir_operand_to_const(frame, operand, locals, env)?;
+                self.cast_to_const(&value, from, frame.mono(self.tcx, *ty))
+            }
+            Rvalue::Aggregate(kind, operands) => {
+                let ty = frame.mono(self.tcx, rvalue.ty(frame.body, self.tcx));
+                let variant = match **kind {
+                    AggregateKind::Adt(_, variant, ..) => variant,
+                    _ => VariantIdx::from_u32(0),
+                };
+                if operands.is_empty() && !ty.is_enum() {
+                    return self.zst_to_const(ty, env);
+                }
+                if !Analyzer::is_mir_datatype(ty) {
+                    return Err(AnalysisError::Unsupported(format!(
+                        "unsupported aggregate of type {:?}",
+                        ty
+                    )));
+                }
+                let mut values = Vec::new();
+                for operand in operands.iter() {
+                    values.push(self.mir_operand_to_const(frame, operand, locals, env)?);
+                }
+                env.variant_value(ty, variant, values)
+            }
+            Rvalue::Discriminant(place) => {
+                let enum_ty = frame.mono(self.tcx, place.ty(frame.body, self.tcx).ty);
+                let TyKind::Adt(adt_def, _) = enum_ty.kind() else {
+                    return Err(AnalysisError::Unsupported(
+                        "discriminant of a non-enum value".to_string(),
+                    ));
+                };
+                let discr_ty = frame.mono(self.tcx, rvalue.ty(frame.body, self.tcx));
+                let value = self.mir_read(frame, place, locals, env)?;
+                let mut discrs = adt_def.discriminants(self.tcx).collect::<Vec<_>>();
+                let (_, last) = discrs.pop().ok_or(AnalysisError::Unsupported(
+                    "enum without variants".to_string(),
+                ))?;
+                let mut result = self.mir_bits_to_const(last.val, discr_ty)?;
+                for (variant, discr) in discrs.into_iter().rev() {
+                    result = format!(
+                        "(ite {} {} {})",
+                        env.variant_test(enum_ty, variant, &value)?,
+                        self.mir_bits_to_const(discr.val, discr_ty)?,
+                        result
+                    );
+                }
+                Ok(result)
+            }
+            _ => Err(AnalysisError::Unsupported(format!(
+                "unsupported rvalue {:?}",
+                rvalue
+            ))),
+        }
+    }
+
+    fn is_mir_datatype(ty: Ty<'tcx>) -> bool {
+        match ty.kind() {
+            TyKind::Tuple(_) => true,
+            TyKind::Adt(adt_def, _) => {
+                (adt_def.is_struct() && !adt_def.is_box()) || adt_def.is_enum()
+            }
+            _ => false,
+        }
+    }
+
+    fn mir_bits_to_const(&self, bits: u128, ty: Ty<'tcx>) -> Result<String, AnalysisError> {
+        let (width, _) = Encoding::int_width(ty).ok_or(AnalysisError::Unsupported(format!(
+            "unsupported switch on {:?}",
+            ty
+        )))?;
+        let lit = ScalarInt::try_from_uint(bits, Size::from_bits(width)).ok_or(
+            AnalysisError::Unsupported("switch value out of range".to_string()),
+        )?;
+        self.scalar_int_to_const(lit, ty)
+    }
+}
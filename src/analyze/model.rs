@@ -0,0 +1,483 @@
+use rustc_middle::mir::{BinOp, UnOp};
+use rustc_middle::ty::{Ty, TyKind};
+use rustc_span::def_id::DefId;
+use rustc_target::abi::VariantIdx;
+
+use crate::analyze::encoding::{Encoding, IntEncoding};
+use crate::analyze::mir::ExternCall;
+use crate::analyze::*;
+
+/// Built-in encodings of `core`/`std` functions.
+#[derive(Clone, Copy, Debug)]
+pub enum Model {
+    Abs,
+    IsPositive,
+    IsNegative,
+    Min,
+    Max,
+    Clamp,
+    Checked(BinOp),
+    Saturating(BinOp),
+    Wrapping(BinOp),
+    Pow,
+    Then,
+    ThenSome,
+    Unwrap,
+    UnwrapErr,
+    Interior(InteriorOp),
+    LocalKeyWith,
+}
+
+/// Operations on the contents of an atomic or a `Cell` through a shared reference. The
+/// memory orderings are irrelevant to a single thread.
+#[derive(Clone, Copy, Debug)]
+pub enum InteriorOp {
+    Load,
+    Store,
+    Swap,
+    Fetch(BinOp),
+    FetchMax,
+    FetchMin,
+    CompareExchange { weak: bool },
+}
+
+/// Models keyed by `def_path_str`. Inherent methods of the integer types share one entry,
+/// with the type written as `{int}`. To model another function, add its path here and, if
+/// needed, a new `Model` case.
+#[rustfmt::skip]
+const MODELS: &[(&str, Model)] = &[
+    ("core::num::<impl {int}>::abs", Model::Abs),
+    ("core::num::<impl {int}>::is_positive", Model::IsPositive),
+    ("core::num::<impl {int}>::is_negative", Model::IsNegative),
+    ("std::cmp::Ord::min", Model::Min),
+    ("std::cmp::min", Model::Min),
+    ("std::cmp::Ord::max", Model::Max),
+    ("std::cmp::max", Model::Max),
+    ("std::cmp::Ord::clamp", Model::Clamp),
+    ("core::num::<impl {int}>::checked_add", Model::Checked(BinOp::Add)),
+    ("core::num::<impl {int}>::checked_sub", Model::Checked(BinOp::Sub)),
+    ("core::num::<impl {int}>::checked_mul", Model::Checked(BinOp::Mul)),
+    ("core::num::<impl {int}>::saturating_add", Model::Saturating(BinOp::Add)),
+    ("core::num::<impl {int}>::saturating_sub", Model::Saturating(BinOp::Sub)),
+    ("core::num::<impl {int}>::saturating_mul", Model::Saturating(BinOp::Mul)),
+    ("core::num::<impl {int}>::wrapping_add", Model::Wrapping(BinOp::Add)),
+    ("core::num::<impl {int}>::wrapping_sub", Model::Wrapping(BinOp::Sub)),
+    ("core::num::<impl {int}>::wrapping_mul", Model::Wrapping(BinOp::Mul)),
+    ("core::num::<impl {int}>::pow", Model::Pow),
+    ("core::bool::<impl bool>::then", Model::Then),
+    ("core::bool::<impl bool>::then_some", Model::ThenSome),
+    ("std::result::Result::<T, E>::unwrap", Model::Unwrap),
+    ("std::result::Result::<T, E>::expect", Model::Unwrap),
+    ("std::result::Result::<T, E>::unwrap_err", Model::UnwrapErr),
+    ("std::sync::atomic::{atomic}::load", Model::Interior(InteriorOp::Load)),
+    ("std::sync::atomic::{atomic}::store", Model::Interior(InteriorOp::Store)),
+    ("std::sync::atomic::{atomic}::swap", Model::Interior(InteriorOp::Swap)),
+    ("std::sync::atomic::{atomic}::fetch_add", Model::Interior(InteriorOp::Fetch(BinOp::Add))),
+    ("std::sync::atomic::{atomic}::fetch_sub", Model::Interior(InteriorOp::Fetch(BinOp::Sub))),
+    ("std::sync::atomic::{atomic}::fetch_and", Model::Interior(InteriorOp::Fetch(BinOp::BitAnd))),
+    ("std::sync::atomic::{atomic}::fetch_or", Model::Interior(InteriorOp::Fetch(BinOp::BitOr))),
+    ("std::sync::atomic::{atomic}::fetch_xor", Model::Interior(InteriorOp::Fetch(BinOp::BitXor))),
+    ("std::sync::atomic::{atomic}::fetch_max", Model::Interior(InteriorOp::FetchMax)),
+    ("std::sync::atomic::{atomic}::fetch_min", Model::Interior(InteriorOp::FetchMin)),
+    ("std::sync::atomic::{atomic}::compare_exchange", Model::Interior(InteriorOp::CompareExchange { weak: false })),
+    ("std::sync::atomic::{atomic}::compare_exchange_weak", Model::Interior(InteriorOp::CompareExchange { weak: true })),
+    ("std::cell::Cell::<T>::get", Model::Interior(InteriorOp::Load)),
+    ("std::cell::Cell::<T>::set", Model::Interior(InteriorOp::Store)),
+    ("std::cell::Cell::<T>::replace", Model::Interior(InteriorOp::Swap)),
+    ("std::thread::LocalKey::<T>::with", Model::LocalKeyWith),
+];
+
+const INT_TYPES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+/// The atomic types share their entries too, written as `{atomic}`.
+const ATOMIC_TYPES: &[&str] = &[
+    "AtomicBool",
+    "AtomicI8",
+    "AtomicI16",
+    "AtomicI32",
+    "AtomicI64",
+    "AtomicIsize",
+    "AtomicU8",
+    "AtomicU16",
+    "AtomicU32",
+    "AtomicU64",
+    "AtomicUsize",
+];
+
+impl<'tcx> Analyzer<'tcx> {
+    pub fn find_model(&self, def_id: DefId) -> Option<Model> {
+        let mut key = self.tcx.def_path_str(def_id);
+        for int in INT_TYPES {
+            key = key.replace(&format!("<impl {}>", int), "<impl {int}>");
+        }
+        for atomic in ATOMIC_TYPES {
+            key = key.replace(&format!("::{}::", atomic), "::{atomic}::");
+        }
+        MODELS
+            .iter()
+            .find(|(path, _)| *path == key)
+            .map(|(_, model)| *model)
+    }
+
+    pub fn model_to_const(
+        &self,
+        model: Model,
+        call: &ExternCall<'_, 'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let values = &call.values;
+        match model {
+            Model::Then | Model::ThenSome => return self.then_to_const(model, call, env),
+            Model::Unwrap | Model::UnwrapErr => return self.unwrap_to_const(model, call, env),
+            Model::Interior(op) => return self.interior_op_to_const(op, call, env),
+            Model::LocalKeyWith => return self.local_key_with_to_const(call, env),
+            _ => {}
+        }
+        let ty = call.tys[0];
+        let (width, signed) = Encoding::int_width(ty).ok_or(AnalysisError::Unsupported(
+            format!("no model of {:?} for {:?}", model, ty),
+        ))?;
+        let zero = self.encoding.int_to_const(0, false, width);
+        match model {
+            Model::Abs => Ok(format!(
+                "(ite {} {} {})",
+                self.binop_to_const(BinOp::Lt, &values[0], &zero, ty)?,
+                self.unop_to_const(UnOp::Neg, &values[0], ty)?,
+                values[0]
+            )),
+            Model::IsPositive => self.binop_to_const(BinOp::Gt, &values[0], &zero, ty),
+            Model::IsNegative => self.binop_to_const(BinOp::Lt, &values[0], &zero, ty),
+            Model::Min => Ok(format!(
+                "(ite {} {} {})",
+                self.binop_to_const(BinOp::Le, &values[0], &values[1], ty)?,
+                values[0],
+                values[1]
+            )),
+            Model::Max => Ok(format!(
+                "(ite {} {} {})",
+                self.binop_to_const(BinOp::Ge, &values[0], &values[1], ty)?,
+                values[0],
+                values[1]
+            )),
+            Model::Clamp => Ok(format!(
+                "(ite {} {} (ite {} {} {}))",
+                self.binop_to_const(BinOp::Lt, &values[0], &values[1], ty)?,
+                values[1],
+                self.binop_to_const(BinOp::Gt, &values[0], &values[2], ty)?,
+                values[2],
+                values[0]
+            )),
+            Model::Checked(op) => {
+                let (exact, wide_ty) = self.exact_binop_to_const(op, &values[0], &values[1], ty)?;
+                let in_range = self.in_range_to_const(&exact, wide_ty, width, signed)?;
+                let (some, none) = Analyzer::option_variants(call.ret_ty, env)?;
+                Ok(format!(
+                    "(ite {} {} {})",
+                    in_range,
+                    env.variant_value(
+                        call.ret_ty,
+                        some,
+                        vec![self.int_to_int(&exact, 128, true, width, signed)]
+                    )?,
+                    env.variant_value(call.ret_ty, none, Vec::new())?
+                ))
+            }
+            Model::Saturating(op) => {
+                let (exact, wide_ty) = self.exact_binop_to_const(op, &values[0], &values[1], ty)?;
+                let (wide_min, wide_max) = self.int_bounds_to_const(width, signed, 128);
+                let (min, max) = self.int_bounds_to_const(width, signed, width);
+                Ok(format!(
+                    "(ite {} {} (ite {} {} {}))",
+                    self.binop_to_const(BinOp::Lt, &exact, &wide_min, wide_ty)?,
+                    min,
+                    self.binop_to_const(BinOp::Gt, &exact, &wide_max, wide_ty)?,
+                    max,
+                    self.int_to_int(&exact, 128, true, width, signed)
+                ))
+            }
+            Model::Wrapping(op) => {
+                let (exact, _) = self.exact_binop_to_const(op, &values[0], &values[1], ty)?;
+                Ok(self.int_to_int(&exact, 128, true, width, signed))
+            }
+            Model::Pow => {
+                let exp = Analyzer::concrete_int(&values[1])
+                    .filter(|exp| *exp <= 64)
+                    .ok_or(AnalysisError::Unsupported(
+                        "pow is only modelled for small constant exponents".to_string(),
+                    ))?;
+                let mut result = self.encoding.int_to_const(1, false, width);
+                for _ in 0..exp {
+                    result = self.binop_to_const(BinOp::Mul, &result, &values[0], ty)?;
+                }
+                Ok(result)
+            }
+            Model::Then
+            | Model::ThenSome
+            | Model::Unwrap
+            | Model::UnwrapErr
+            | Model::Interior(_)
+            | Model::LocalKeyWith => unreachable!(),
+        }
+    }
+
+    /// `bool::then(f)` and `bool::then_some(value)`: `Some` of the value if the receiver is
+    /// true. The closure of `then` only runs on that branch.
+    fn then_to_const(
+        &self,
+        model: Model,
+        call: &ExternCall<'_, 'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let cond = call.values[0].clone();
+        let (some, none) = Analyzer::option_variants(call.ret_ty, env)?;
+        let none_value = env.variant_value(call.ret_ty, none, Vec::new())?;
+        if let Model::ThenSome = model {
+            let some_value = env.variant_value(call.ret_ty, some, vec![call.values[1].clone()])?;
+            return Ok(format!("(ite {} {} {})", cond, some_value, none_value));
+        }
+        let (Some(exprs), TyKind::Closure(closure_id, closure_args)) =
+            (call.exprs, call.tys[1].kind())
+        else {
+            return Err(AnalysisError::Unsupported(
+                "bool::then is only modelled for closures of the analyzed crate".to_string(),
+            ));
+        };
+        let rthir = self.get_local_instance(closure_id, closure_args)?.ok_or(
+            AnalysisError::Unsupported("No RThir body Found".to_string()),
+        )?;
+        let unit: Ty<'tcx> = self.tcx.types.unit;
+        let no_args = Rc::new(RExpr::new(
+            RExprKind::Tuple {
+                fields: Box::new([]),
+            },
+            unit,
+            call.call.span,
+        ));
+        let mut then_env = env.new_env_from_str("then".to_string(), call.call.span)?;
+        then_env.add_smt_command(cond.clone(), call.call.clone());
+        let value = self.closure_call_to_const(
+            *closure_id,
+            rthir,
+            &[exprs[1].clone(), no_args],
+            &mut then_env,
+        )?;
+        let some_value = env.variant_value(call.ret_ty, some, vec![value])?;
+        env.merge_ite_env(&cond, then_env, None)?;
+        Ok(format!("(ite {} {} {})", cond, some_value, none_value))
+    }
+
+    /// `Result::unwrap`, `expect` and `unwrap_err`: the payload of the expected variant.
+    /// They panic on the other one, which must be unreachable.
+    fn unwrap_to_const(
+        &self,
+        model: Model,
+        call: &ExternCall<'_, 'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let ty = call.tys[0];
+        let name = match model {
+            Model::UnwrapErr => "Err",
+            _ => "Ok",
+        };
+        let variant = env
+            .variant_by_name(ty, name)
+            .ok_or(AnalysisError::Unsupported(format!(
+                "{:?} is not a Result",
+                ty
+            )))?;
+        let expected = env.variant_test(ty, variant, &call.values[0])?;
+        self.verify_no_panic(&call.conds, expected, call.call.span, env)?;
+        let datatype = env.ty_to_sort(ty)?;
+        Ok(format!(
+            "({} {})",
+            env.variant_selector_name(&datatype, ty, variant, 0),
+            call.values[0]
+        ))
+    }
+
+    /// Reads and writes the contents of the receiver. `AtomicBool` holds a `u8`.
+    fn interior_op_to_const(
+        &self,
+        op: InteriorOp,
+        call: &ExternCall<'_, 'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let Some(exprs) = call.exprs else {
+            return Err(AnalysisError::Unsupported(
+                "interior mutability is only modelled for calls of the analyzed crate".to_string(),
+            ));
+        };
+        let target = self.ref_target(exprs[0].clone(), env)?;
+        let (place, inner_ty) = self.interior_place(target, call.tys[0].peel_refs())?;
+        let ty = match op {
+            InteriorOp::Load => call.ret_ty,
+            _ => call.tys[1],
+        };
+        let stored = env.read_place(&place)?;
+        let (old, to_inner) = if ty.is_bool() && inner_ty.is_integral() {
+            let (width, _) = Encoding::int_width(inner_ty).expect("integer type has a width");
+            let zero = self.encoding.int_to_const(0, false, width);
+            let one = self.encoding.int_to_const(1, false, width);
+            (
+                self.binop_to_const(BinOp::Ne, &stored, &zero, inner_ty)?,
+                Some((one, zero)),
+            )
+        } else {
+            (stored, None)
+        };
+        let (new, result) = match op {
+            InteriorOp::Load => (None, old),
+            InteriorOp::Store => (Some(call.values[1].clone()), "unit".to_string()),
+            InteriorOp::Swap => (Some(call.values[1].clone()), old),
+            InteriorOp::Fetch(bin_op) if ty.is_bool() => (
+                Some(self.binop_to_const(bin_op, &old, &call.values[1], ty)?),
+                old,
+            ),
+            InteriorOp::Fetch(bin_op) => {
+                let (width, signed) = Encoding::int_width(ty).ok_or(AnalysisError::Unsupported(
+                    format!("no model of {:?} for {:?}", op, ty),
+                ))?;
+                let (exact, _) = self.exact_binop_to_const(bin_op, &old, &call.values[1], ty)?;
+                (Some(self.int_to_int(&exact, 128, true, width, signed)), old)
+            }
+            InteriorOp::FetchMax | InteriorOp::FetchMin => {
+                let cmp = match op {
+                    InteriorOp::FetchMax => BinOp::Ge,
+                    _ => BinOp::Le,
+                };
+                let new = format!(
+                    "(ite {} {} {})",
+                    self.binop_to_const(cmp, &old, &call.values[1], ty)?,
+                    old,
+                    call.values[1]
+                );
+                (Some(new), old)
+            }
+            InteriorOp::CompareExchange { weak } => {
+                let mut success = self.binop_to_const(BinOp::Eq, &old, &call.values[1], ty)?;
+                // The weak version may fail spuriously.
+                if weak {
+                    let name = env.fresh_name("spurious".to_string(), call.call.span);
+                    env.add_random_var(self.tcx.types.bool, name.clone());
+                    success = format!("(and {} (not {}))", success, name);
+                }
+                let (ok, err) = match (
+                    env.variant_by_name(call.ret_ty, "Ok"),
+                    env.variant_by_name(call.ret_ty, "Err"),
+                ) {
+                    (Some(ok), Some(err)) => (ok, err),
+                    _ => {
+                        return Err(AnalysisError::Unsupported(format!(
+                            "{:?} is not a Result",
+                            call.ret_ty
+                        )))
+                    }
+                };
+                let result = format!(
+                    "(ite {} {} {})",
+                    success,
+                    env.variant_value(call.ret_ty, ok, vec![old.clone()])?,
+                    env.variant_value(call.ret_ty, err, vec![old.clone()])?
+                );
+                let new = format!("(ite {} {} {})", success, call.values[2], old);
+                (Some(new), result)
+            }
+        };
+        if let Some(new) = new {
+            let new = match to_inner {
+                Some((one, zero)) => format!("(ite {} {} {})", new, one, zero),
+                None => new,
+            };
+            env.write_place(&place, new)?;
+        }
+        Ok(result)
+    }
+
+    fn option_variants(
+        ty: Ty<'tcx>,
+        env: &Env<'tcx>,
+    ) -> Result<(VariantIdx, VariantIdx), AnalysisError> {
+        match (
+            env.variant_by_name(ty, "Some"),
+            env.variant_by_name(ty, "None"),
+        ) {
+            (Some(some), Some(none)) => Ok((some, none)),
+            _ => Err(AnalysisError::Unsupported(format!(
+                "{:?} is not an Option",
+                ty
+            ))),
+        }
+    }
+
+    /// `lhs op rhs` without overflow, and the type it is computed in: 128-bit in the
+    /// bit-vector encoding.
+    fn exact_binop_to_const(
+        &self,
+        op: BinOp,
+        lhs: &str,
+        rhs: &str,
+        ty: Ty<'tcx>,
+    ) -> Result<(String, Ty<'tcx>), AnalysisError> {
+        if self.encoding.int == IntEncoding::Math {
+            return Ok((self.binop_to_const(op, lhs, rhs, ty)?, ty));
+        }
+        let (width, _) = Encoding::int_width(ty).expect("integer type has a width");
+        if width > 64 || (width > 32 && op == BinOp::Mul && !ty.is_signed()) {
+            return Err(AnalysisError::Unsupported(format!(
+                "overflow of {:?} on {:?} is not modelled with bit-vectors",
+                op, ty
+            )));
+        }
+        let wide_ty = self.tcx.types.i128;
+        let lhs = self.cast_to_const(lhs, ty, wide_ty)?;
+        let rhs = self.cast_to_const(rhs, ty, wide_ty)?;
+        Ok((self.binop_to_const(op, &lhs, &rhs, wide_ty)?, wide_ty))
+    }
+
+    fn in_range_to_const(
+        &self,
+        value: &str,
+        wide_ty: Ty<'tcx>,
+        width: u64,
+        signed: bool,
+    ) -> Result<String, AnalysisError> {
+        let (min, max) = self.int_bounds_to_const(width, signed, 128);
+        Ok(format!(
+            "(and {} {})",
+            self.binop_to_const(BinOp::Le, &min, value, wide_ty)?,
+            self.binop_to_const(BinOp::Le, value, &max, wide_ty)?
+        ))
+    }
+
+    /// The bounds of an integer type, as values of `in_width` bits.
+    fn int_bounds_to_const(&self, width: u64, signed: bool, in_width: u64) -> (String, String) {
+        if signed {
+            (
+                self.encoding.int_to_const(1 << (width - 1), true, in_width),
+                self.encoding
+                    .int_to_const((1 << (width - 1)) - 1, false, in_width),
+            )
+        } else {
+            (
+                self.encoding.int_to_const(0, false, in_width),
+                self.encoding
+                    .int_to_const(u128::MAX >> (128 - width), false, in_width),
+            )
+        }
+    }
+
+    /// The value of an integer literal in either encoding.
+    fn concrete_int(value: &str) -> Option<u128> {
+        if let Ok(value) = value.parse::<u128>() {
+            return Some(value);
+        }
+        value
+            .strip_prefix("(_ bv")?
+            .split(' ')
+            .next()?
+            .parse::<u128>()
+            .ok()
+    }
+}
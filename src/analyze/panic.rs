@@ -0,0 +1,131 @@
+use rustc_hir::def::DefKind;
+use rustc_middle::mir::BinOp;
+use rustc_middle::ty::Ty;
+use rustc_span::def_id::DefId;
+use rustc_span::Span;
+
+use crate::analyze::*;
+
+/// Diverging functions of the standard library that end the program without panicking.
+const EXITS: &[&str] = &[
+    "std::process::exit",
+    "std::process::abort",
+    "std::intrinsics::abort",
+    "core::intrinsics::abort",
+];
+
+/// Panics are verification obligations: `panic!`, `assert!`, `unreachable!`, failed
+/// `unwrap`s and out-of-bounds indexing must be unreachable on every path. Any diverging
+/// expression ends the path it is on.
+impl<'tcx> Analyzer<'tcx> {
+    /// Functions of `core`, `std` and `alloc` returning `!`, e.g. `core::panicking::panic`,
+    /// `core::option::unwrap_failed` or `std::process::exit`.
+    pub fn is_std_diverging_fn(&self, def_id: DefId) -> bool {
+        matches!(
+            self.tcx.crate_name(def_id.krate).as_str(),
+            "core" | "std" | "alloc"
+        ) && matches!(self.tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+            && self
+                .tcx
+                .fn_sig(def_id)
+                .skip_binder()
+                .output()
+                .skip_binder()
+                .is_never()
+    }
+
+    pub fn is_panic_fn(&self, def_id: DefId) -> bool {
+        self.is_std_diverging_fn(def_id) && !EXITS.contains(&self.tcx.def_path_str(def_id).as_str())
+    }
+
+    /// Calls and loops of type `!`. `return`, `break` and `continue` leave the current
+    /// body instead.
+    pub fn diverges(expr: &RExpr<'tcx>) -> bool {
+        expr.ty.is_never() && matches!(expr.kind, RExprKind::Call { .. } | RExprKind::Loop { .. })
+    }
+
+    /// A call of a diverging function of the standard library. The arguments, usually
+    /// messages, are not evaluated.
+    pub fn std_diverging_call_to_const(
+        &self,
+        def_id: DefId,
+        call: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        if self.is_panic_fn(def_id) {
+            self.verify_no_panic(&[], "false".to_string(), call.span, env)?;
+        }
+        self.zst_to_const(self.tcx.types.unit, env)
+    }
+
+    /// The value of a diverging expression coerced to another type: it is never used.
+    pub fn never_to_const(
+        &self,
+        expr: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        if expr.ty.is_unit() || expr.ty.is_never() {
+            self.zst_to_const(self.tcx.types.unit, env)
+        } else {
+            Ok(self.havoc_result(expr, env))
+        }
+    }
+
+    /// Proves `constraint` under the current path and the branch conditions `conds` of the
+    /// MIR bodies being evaluated.
+    pub fn verify_no_panic(
+        &self,
+        conds: &[String],
+        constraint: String,
+        span: Span,
+        env: &mut Env<'tcx>,
+    ) -> Result<(), AnalysisError> {
+        println!(
+            "Checking that the code at {:?} cannot panic",
+            span.source_callsite()
+        );
+        let constraint = if conds.is_empty() {
+            constraint
+        } else {
+            format!("(=> (and {}) {})", conds.join(" "), constraint)
+        };
+        env.verify_z3(constraint, span)
+    }
+
+    /// The check of `lhs / divisor` and `lhs % divisor` on integers.
+    pub fn verify_divisor(
+        &self,
+        divisor: &str,
+        ty: Ty<'tcx>,
+        span: Span,
+        env: &mut Env<'tcx>,
+    ) -> Result<(), AnalysisError> {
+        let (width, _) = Encoding::int_width(ty).expect("integer type has a width");
+        let zero = self.encoding.int_to_const(0, false, width);
+        let nonzero = format!(
+            "(not {})",
+            self.binop_to_const(BinOp::Eq, divisor, &zero, ty)?
+        );
+        self.verify_no_panic(&[], nonzero, span, env)
+    }
+
+    /// The bounds check of `lhs[index]` on an array.
+    pub fn verify_index(
+        &self,
+        lhs_ty: Ty<'tcx>,
+        index: &str,
+        span: Span,
+        env: &mut Env<'tcx>,
+    ) -> Result<(), AnalysisError> {
+        let len = self.array_len(lhs_ty)?;
+        let usize_ty = self.tcx.types.usize;
+        let zero = self.encoding.int_to_const(0, false, 64);
+        let len = self.encoding.int_to_const(len as u128, false, 64);
+        let in_bounds = format!(
+            "(and {} {})",
+            self.binop_to_const(BinOp::Le, &zero, index, usize_ty)?,
+            self.binop_to_const(BinOp::Lt, index, &len, usize_ty)?
+        );
+        self.verify_no_panic(&[], in_bounds, span, env)
+    }
+}
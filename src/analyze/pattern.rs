@@ -0,0 +1,419 @@
+use rustc_hir::{BindingMode, ByRef, RangeEnd};
+use rustc_middle::mir::BinOp;
+use rustc_middle::thir::PatRangeBoundary;
+use rustc_middle::ty::{Mutability, ParamEnv, Ty, TyKind};
+use rustc_span::Symbol;
+use rustc_target::abi::{FieldIdx, VariantIdx};
+
+use crate::analyze::*;
+
+/// A variable bound by a pattern and the part of the matched value it is bound to.
+struct PatBinding<'tcx> {
+    name: Symbol,
+    var: LocalVarId,
+    ty: Ty<'tcx>,
+    pat: Rc<RExpr<'tcx>>,
+    value: String,
+}
+
+/// Evaluates the body of a `match` arm.
+pub type ArmEval<'a, 'tcx> =
+    &'a dyn Fn(&Analyzer<'tcx>, Rc<RExpr<'tcx>>, &mut Env<'tcx>) -> Result<String, AnalysisError>;
+
+/// A pattern is encoded as a test on the value it matches, and its bindings as selections
+/// from that value. Selections are total in SMT, so a binding is only meaningful where the
+/// test holds.
+impl<'tcx> Analyzer<'tcx> {
+    /// Whether `value` matches `pat`.
+    pub fn pattern_test(
+        &self,
+        pat: &RExpr<'tcx>,
+        value: &str,
+        env: &Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        use RPatKind::*;
+        match Analyzer::pat_kind(pat)? {
+            Wild
+            | Binding {
+                subpattern: None, ..
+            } => Ok("true".to_string()),
+            // Shared references are encoded as the value they point to.
+            Binding {
+                subpattern: Some(subpattern),
+                ..
+            }
+            | AscribeUserType { subpattern, .. }
+            | Deref { subpattern } => self.pattern_test(subpattern, value, env),
+            Variant {
+                variant_index,
+                subpatterns,
+                ..
+            } => {
+                let mut tests = vec![env.variant_test(pat.ty, *variant_index, value)?];
+                for (field, subpattern) in subpatterns.iter() {
+                    let field_value =
+                        Analyzer::pattern_field(pat.ty, *variant_index, *field, value, env)?;
+                    tests.push(self.pattern_test(subpattern, &field_value, env)?);
+                }
+                Ok(Analyzer::conjunction(tests))
+            }
+            Leaf { subpatterns } => {
+                let mut tests = Vec::new();
+                for (field, subpattern) in subpatterns.iter() {
+                    let field_value =
+                        Analyzer::pattern_field(pat.ty, VariantIdx::ZERO, *field, value, env)?;
+                    tests.push(self.pattern_test(subpattern, &field_value, env)?);
+                }
+                Ok(Analyzer::conjunction(tests))
+            }
+            Constant { value: constant } => {
+                let constant = self.mir_const_to_const(*constant, pat.ty)?;
+                self.binop_to_const(BinOp::Eq, value, &constant, pat.ty)
+            }
+            Range(range) => {
+                let mut tests = Vec::new();
+                if let PatRangeBoundary::Finite(lo) = range.lo {
+                    let lo = self.mir_const_to_const(lo, range.ty)?;
+                    tests.push(self.binop_to_const(BinOp::Le, &lo, value, range.ty)?);
+                }
+                if let PatRangeBoundary::Finite(hi) = range.hi {
+                    let hi = self.mir_const_to_const(hi, range.ty)?;
+                    let op = match range.end {
+                        RangeEnd::Included => BinOp::Le,
+                        RangeEnd::Excluded => BinOp::Lt,
+                    };
+                    tests.push(self.binop_to_const(op, value, &hi, range.ty)?);
+                }
+                Ok(Analyzer::conjunction(tests))
+            }
+            Array { .. } => {
+                let mut tests = Vec::new();
+                for (subpattern, element) in self.array_elements(pat, value)? {
+                    tests.push(self.pattern_test(&subpattern, &element, env)?);
+                }
+                Ok(Analyzer::conjunction(tests))
+            }
+            Slice => Err(AnalysisError::Unsupported(
+                "Slice patterns are not supported".to_string(),
+            )),
+            Or { pats } => {
+                let mut tests = Vec::new();
+                for pat in pats.iter() {
+                    tests.push(self.pattern_test(pat, value, env)?);
+                }
+                Ok(format!("(or {})", tests.join(" ")))
+            }
+            _ => Err(AnalysisError::Unsupported(format!(
+                "Unsupported pattern {:?}",
+                pat.kind
+            ))),
+        }
+    }
+
+    /// Binds the variables of `pat` to the parts of `value` they match.
+    pub fn bind_pattern(
+        &self,
+        pat: Rc<RExpr<'tcx>>,
+        value: String,
+        env: &mut Env<'tcx>,
+    ) -> Result<(), AnalysisError> {
+        let mut bindings = Vec::new();
+        self.pattern_bindings(pat, value, env, &mut bindings)?;
+        for binding in bindings {
+            let name = Analyzer::get_name_from_span(binding.pat.span);
+            env.add_param(name, binding.ty, binding.var, binding.pat.clone());
+            env.assign_value(binding.var, binding.value, binding.pat);
+        }
+        Ok(())
+    }
+
+    fn pattern_bindings(
+        &self,
+        pat: Rc<RExpr<'tcx>>,
+        value: String,
+        env: &Env<'tcx>,
+        bindings: &mut Vec<PatBinding<'tcx>>,
+    ) -> Result<(), AnalysisError> {
+        use RPatKind::*;
+        match Analyzer::pat_kind(&pat)? {
+            Binding {
+                mode: BindingMode(ByRef::Yes(Mutability::Mut), _),
+                ..
+            } => {
+                return Err(AnalysisError::Unsupported(
+                    "Mutable reference bindings in patterns are not supported".to_string(),
+                ))
+            }
+            Binding {
+                name,
+                var,
+                ty,
+                subpattern,
+                ..
+            } => {
+                bindings.push(PatBinding {
+                    name: *name,
+                    var: *var,
+                    ty: *ty,
+                    pat: pat.clone(),
+                    value: value.clone(),
+                });
+                if let Some(subpattern) = subpattern {
+                    self.pattern_bindings(subpattern.clone(), value, env, bindings)?;
+                }
+            }
+            AscribeUserType { subpattern, .. } | Deref { subpattern } => {
+                self.pattern_bindings(subpattern.clone(), value, env, bindings)?;
+            }
+            Variant {
+                variant_index,
+                subpatterns,
+                ..
+            } => {
+                for (field, subpattern) in subpatterns.iter() {
+                    let field_value =
+                        Analyzer::pattern_field(pat.ty, *variant_index, *field, &value, env)?;
+                    self.pattern_bindings(subpattern.clone(), field_value, env, bindings)?;
+                }
+            }
+            Leaf { subpatterns } => {
+                for (field, subpattern) in subpatterns.iter() {
+                    let field_value =
+                        Analyzer::pattern_field(pat.ty, VariantIdx::ZERO, *field, &value, env)?;
+                    self.pattern_bindings(subpattern.clone(), field_value, env, bindings)?;
+                }
+            }
+            Array { .. } => {
+                for (subpattern, element) in self.array_elements(&pat, &value)? {
+                    self.pattern_bindings(subpattern, element, env, bindings)?;
+                }
+            }
+            // Every alternative binds the same names; a name takes its value from the first
+            // alternative that matches.
+            Or { pats } => {
+                let mut alternatives = Vec::new();
+                for pat in pats.iter() {
+                    let mut alt_bindings = Vec::new();
+                    self.pattern_bindings(pat.clone(), value.clone(), env, &mut alt_bindings)?;
+                    alternatives.push((self.pattern_test(pat, &value, env)?, alt_bindings));
+                }
+                let (_, mut merged) = alternatives.pop().expect("or-patterns are not empty");
+                for (test, alt_bindings) in alternatives.into_iter().rev() {
+                    for binding in merged.iter_mut() {
+                        if let Some(alt) = alt_bindings.iter().find(|b| b.name == binding.name) {
+                            binding.value =
+                                format!("(ite {} {} {})", test, alt.value, binding.value);
+                            binding.var = alt.var;
+                            binding.pat = alt.pat.clone();
+                        }
+                    }
+                }
+                bindings.extend(merged);
+            }
+            Wild | Constant { .. } | Range(_) => (),
+            Slice => {
+                return Err(AnalysisError::Unsupported(
+                    "Slice patterns are not supported".to_string(),
+                ))
+            }
+            _ => {
+                return Err(AnalysisError::Unsupported(format!(
+                    "Unsupported pattern {:?}",
+                    pat.kind
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn pat_kind<'a>(pat: &'a RExpr<'tcx>) -> Result<&'a RPatKind<'tcx>, AnalysisError> {
+        match &pat.kind {
+            RExprKind::Pat { kind } => Ok(kind),
+            _ => Err(AnalysisError::Unsupported(
+                "Unsupported expression in pattern".to_string(),
+            )),
+        }
+    }
+
+    /// A field of a struct, tuple or enum variant being matched.
+    fn pattern_field(
+        ty: Ty<'tcx>,
+        variant: VariantIdx,
+        field: FieldIdx,
+        value: &str,
+        env: &Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let datatype = env.ty_to_sort(ty)?;
+        Ok(format!(
+            "({} {})",
+            env.variant_selector_name(&datatype, ty, variant, field.as_usize()),
+            value
+        ))
+    }
+
+    /// The subpatterns of `[a, b, .., z]` with the array elements they match.
+    fn array_elements(
+        &self,
+        pat: &RExpr<'tcx>,
+        value: &str,
+    ) -> Result<Vec<(Rc<RExpr<'tcx>>, String)>, AnalysisError> {
+        let RExprKind::Pat {
+            kind:
+                RPatKind::Array {
+                    prefix,
+                    slice,
+                    suffix,
+                },
+        } = &pat.kind
+        else {
+            unreachable!()
+        };
+        if let Some(slice) = slice {
+            if !matches!(Analyzer::pat_kind(slice)?, RPatKind::Wild) {
+                return Err(AnalysisError::Unsupported(
+                    "Bindings of subslices are not supported".to_string(),
+                ));
+            }
+        }
+        let len = self.array_len(pat.ty)?;
+        let indices = (0..prefix.len() as u64).chain(len - suffix.len() as u64..len);
+        Ok(prefix
+            .iter()
+            .chain(suffix.iter())
+            .zip(indices)
+            .map(|(subpattern, idx)| {
+                let idx = self.encoding.int_to_const(idx as u128, false, 64);
+                (subpattern.clone(), format!("(select {} {})", value, idx))
+            })
+            .collect())
+    }
+
+    pub fn array_len(&self, ty: Ty<'tcx>) -> Result<u64, AnalysisError> {
+        let TyKind::Array(_, len) = ty.peel_refs().kind() else {
+            return Err(AnalysisError::Unsupported(format!(
+                "Indexing of {:?} is not supported",
+                ty
+            )));
+        };
+        len.try_eval_target_usize(self.tcx, ParamEnv::reveal_all())
+            .ok_or(AnalysisError::Unsupported(
+                "Arrays of unknown length are not supported".to_string(),
+            ))
+    }
+
+    fn conjunction(tests: Vec<String>) -> String {
+        let tests: Vec<String> = tests.into_iter().filter(|test| test != "true").collect();
+        match tests.len() {
+            0 => "true".to_string(),
+            1 => tests[0].clone(),
+            _ => format!("(and {})", tests.join(" ")),
+        }
+    }
+
+    /// `let PAT = expr` in the condition of an `if` or `while`. The bindings are made on the
+    /// current path; only the branch where the test holds uses them.
+    pub fn let_binding_to_const(
+        &self,
+        scrutinee: Rc<RExpr<'tcx>>,
+        pat: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let value = self.expr_to_const(scrutinee, env)?;
+        let test = self.pattern_test(&pat, &value, env)?;
+        self.bind_pattern(pat, value, env)?;
+        Ok(test)
+    }
+
+    /// `let PAT = init;` with a destructuring pattern, or `let PAT = init else { .. };`. The
+    /// else block diverges or returns, so the rest of the body runs where the pattern
+    /// matched.
+    pub fn analyze_let_pattern(
+        &self,
+        pattern: Rc<RExpr<'tcx>>,
+        init: Option<Rc<RExpr<'tcx>>>,
+        else_block: Option<Rc<RExpr<'tcx>>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<(), AnalysisError> {
+        let Some(init) = init else {
+            return Err(AnalysisError::Unsupported(
+                "Uninitialized let statements with patterns are not supported".to_string(),
+            ));
+        };
+        let value = self.expr_to_const(init.clone(), env)?;
+        if let Some(else_block) = else_block {
+            let test = self.pattern_test(&pattern, &value, env)?;
+            let not_test = format!("(not {})", test);
+            let mut else_env = env.new_env_from_str("else".to_string(), else_block.span)?;
+            else_env.add_smt_command(not_test.clone(), init.clone());
+            self.analyze_body(else_block, &mut else_env)?;
+            env.merge_ite_env(&not_test, else_env, None)?;
+        }
+        self.bind_pattern(pattern, value, env)
+    }
+
+    /// `match` as a chain of branches: an arm is taken where its pattern and guard hold and
+    /// no earlier arm was taken.
+    pub fn match_to_const(
+        &self,
+        scrutinee: Rc<RExpr<'tcx>>,
+        arms: &[Rc<RExpr<'tcx>>],
+        eval: ArmEval<'_, 'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let value = self.expr_to_const(scrutinee, env)?;
+        self.arms_to_const(&value, arms, eval, env)
+    }
+
+    fn arms_to_const(
+        &self,
+        value: &str,
+        arms: &[Rc<RExpr<'tcx>>],
+        eval: ArmEval<'_, 'tcx>,
+        env: &mut Env<'tcx>,
+    ) -> Result<String, AnalysisError> {
+        let Some((arm, rest)) = arms.split_first() else {
+            return Err(AnalysisError::Unsupported(
+                "Matches without arms are not supported".to_string(),
+            ));
+        };
+        let RExprKind::Arm {
+            pattern,
+            guard,
+            body,
+        } = &arm.kind
+        else {
+            unreachable!()
+        };
+        // Matches are exhaustive, so the last arm is taken wherever the others are not.
+        if guard.is_none() && rest.is_empty() {
+            self.bind_pattern(pattern.clone(), value.to_string(), env)?;
+            return eval(self, body.clone(), env);
+        }
+
+        let test = self.pattern_test(pattern, value, env)?;
+        let mut arm_env = env.new_env_from_str("arm".to_string(), arm.span)?;
+        arm_env.add_smt_command(test.clone(), arm.clone());
+        self.bind_pattern(pattern.clone(), value.to_string(), &mut arm_env)?;
+        let cond = match guard {
+            Some(guard) => {
+                let guard = self.expr_to_const(guard.clone(), &mut arm_env)?;
+                arm_env.add_smt_command(guard.clone(), arm.clone());
+                format!("(and {} {})", test, guard)
+            }
+            None => test,
+        };
+        let then_str = eval(self, body.clone(), &mut arm_env)?;
+
+        let mut else_env = env.new_env_from_str("else".to_string(), arm.span)?;
+        else_env.add_smt_command(format!("(not {})", cond), arm.clone());
+        let else_str = if rest.is_empty() {
+            // Only reached where the guard of the last arm fails, which exhaustiveness rules
+            // out.
+            self.never_to_const(arm.clone(), &mut else_env)?
+        } else {
+            self.arms_to_const(value, rest, eval, &mut else_env)?
+        };
+        env.merge_ite_env(&cond, arm_env, Some(else_env))?;
+        Ok(format!("(ite {} {} {})", cond, then_str, else_str))
+    }
+}
@@ -0,0 +1,416 @@
+use rustc_middle::mir::BorrowKind;
+use rustc_middle::ty::{Mutability, ParamEnv, Ty, TyKind};
+use rustc_span::def_id::DefId;
+use rustc_target::abi::FieldIdx;
+
+use crate::analyze::*;
+
+/// A memory location that a `&mut` reference, or a shared reference to an interior-mutable
+/// value, points to. Calls are inlined, so the target of
+/// every mutable reference is known up to the branch conditions under which it was created.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Place<'tcx> {
+    Var(LocalVarId),
+    /// A piece of the global state, see `Env::statics`.
+    Static(DefId),
+    Field {
+        base: Box<Place<'tcx>>,
+        ty: Ty<'tcx>,
+        idx: FieldIdx,
+    },
+    Index {
+        base: Box<Place<'tcx>>,
+        index: String,
+    },
+    Ite {
+        cond: String,
+        then: Box<Place<'tcx>>,
+        else_: Box<Place<'tcx>>,
+    },
+}
+
+impl<'tcx> Analyzer<'tcx> {
+    pub fn is_mut_ref(ty: Ty<'tcx>) -> bool {
+        matches!(ty.kind(), TyKind::Ref(_, _, Mutability::Mut))
+    }
+
+    /// Shared references to structs with interior mutability, e.g. `&AtomicUsize` or
+    /// `&Cell<T>`. They can be written through, so they are resolved to the place they
+    /// borrow like `&mut` references.
+    pub fn is_cell_ref(&self, ty: Ty<'tcx>) -> bool {
+        matches!(ty.kind(), TyKind::Ref(_, inner_ty, Mutability::Not)
+            if inner_ty.is_adt() && !inner_ty.is_freeze(self.tcx, ParamEnv::reveal_all()))
+    }
+
+    pub fn expr_to_place(
+        &self,
+        expr: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<Place<'tcx>, AnalysisError> {
+        use RExprKind::*;
+        match &expr.kind {
+            VarRef { id } => Ok(Place::Var(*id)),
+            UpvarRef {
+                closure_def_id,
+                var_hir_id,
+            } => Ok(self
+                .upvar_place(*closure_def_id, *var_hir_id, env)?
+                .unwrap_or(Place::Var(*var_hir_id))),
+            Deref { arg }
+                if Analyzer::is_mut_ref(arg.ty)
+                    || self.is_cell_ref(arg.ty)
+                    || matches!(arg.kind, StaticRef { .. }) =>
+            {
+                self.ref_target(arg.clone(), env)
+            }
+            Field { lhs, name, .. } => Ok(Place::Field {
+                base: Box::new(self.expr_to_place(lhs.clone(), env)?),
+                ty: lhs.ty,
+                idx: *name,
+            }),
+            Index { lhs, index } => {
+                let index = self.expr_to_const(index.clone(), env)?;
+                self.verify_index(lhs.ty, &index, expr.span, env)?;
+                Ok(Place::Index {
+                    base: Box::new(self.expr_to_place(lhs.clone(), env)?),
+                    index,
+                })
+            }
+            PlaceTypeAscription { source, .. } => self.expr_to_place(source.clone(), env),
+            _ => Err(AnalysisError::Unsupported(format!(
+                "Unsupported place expression {:?}",
+                expr.kind
+            ))),
+        }
+    }
+
+    /// Resolves an expression of type `&mut T`, or a shared reference to an interior-mutable
+    /// `T`, to the place it borrows.
+    pub fn ref_target(
+        &self,
+        expr: Rc<RExpr<'tcx>>,
+        env: &mut Env<'tcx>,
+    ) -> Result<Place<'tcx>, AnalysisError> {
+        use RExprKind::*;
+        match &expr.kind {
+            VarRef { id } | UpvarRef { var_hir_id: id, .. } => env
+                .ref_map
+                .get(id)
+                .cloned()
+                .ok_or(AnalysisError::Unsupported(format!(
+                    "Reference {:?} is not bound to a place",
+                    id
+                ))),
+            Borrow { arg, .. } => self.expr_to_place(arg.clone(), env),
+            StaticRef { def_id } if env.statics.contains_key(def_id) => {
+                Ok(Place::Static(*def_id))
+            }
+            Block {
+                stmts,
+                expr: Some(value),
+            } if stmts.is_empty() => self.ref_target(value.clone(), env),
+            If {
+                cond,
+                then,
+                else_opt: Some(else_expr),
+            } => Ok(Place::Ite {
+                cond: self.expr_to_const(cond.clone(), env)?,
+                then: Box::new(self.ref_target(then.clone(), env)?),
+                else_: Box::new(self.ref_target(else_expr.clone(), env)?),
+            }),
+            _ => Err(AnalysisError::Unsupported(format!(
+                "Unsupported mutable reference {:?}",
+                expr.kind
+            ))),
+        }
+    }
+
+    /// The variables and statics that `expr` may write: those it assigns, borrows mutably,
+    /// or reaches through a `&mut` or interior-mutable reference. Only the syntax is looked
+    /// at, so writes made inside the functions it calls are not included.
+    pub fn written_places(
+        &self,
+        expr: &RExpr<'tcx>,
+        env: &Env<'tcx>,
+        places: &mut Vec<Place<'tcx>>,
+    ) {
+        use RExprKind::*;
+        match &expr.kind {
+            Assign { lhs, .. } | AssignOp { lhs, .. } => self.place_roots(lhs, env, places),
+            Borrow {
+                borrow_kind: BorrowKind::Mut { .. },
+                arg,
+            }
+            | AddressOf {
+                mutability: Mutability::Mut,
+                arg,
+            } => self.place_roots(arg, env, places),
+            Borrow { arg, .. } if self.is_cell_ref(expr.ty) => self.place_roots(arg, env, places),
+            VarRef { id } | UpvarRef { var_hir_id: id, .. }
+                if Analyzer::is_mut_ref(expr.ty) || self.is_cell_ref(expr.ty) =>
+            {
+                if let Some(place) = env.ref_map.get(id) {
+                    place.roots(places);
+                }
+            }
+            // The slot of a `thread_local!` key can only be reached through `with`.
+            NamedConst { def_id, .. } if env.statics.contains_key(def_id) => {
+                places.push(Place::Static(*def_id))
+            }
+            _ => {}
+        }
+        match &expr.kind {
+            If {
+                cond,
+                then,
+                else_opt,
+            } => {
+                self.written_places(cond, env, places);
+                self.written_places(then, env, places);
+                if let Some(else_expr) = else_opt {
+                    self.written_places(else_expr, env, places);
+                }
+            }
+            Call { fun, args, .. } => {
+                self.written_places(fun, env, places);
+                for arg in args.iter() {
+                    self.written_places(arg, env, places);
+                }
+            }
+            Binary { lhs, rhs, .. }
+            | LogicalOp { lhs, rhs, .. }
+            | Assign { lhs, rhs }
+            | AssignOp { lhs, rhs, .. }
+            | Index { lhs, index: rhs } => {
+                self.written_places(lhs, env, places);
+                self.written_places(rhs, env, places);
+            }
+            Deref { arg }
+            | Unary { arg, .. }
+            | Borrow { arg, .. }
+            | AddressOf { arg, .. }
+            | Cast { source: arg }
+            | NeverToAny { source: arg }
+            | PointerCoercion { source: arg, .. }
+            | PlaceTypeAscription { source: arg, .. }
+            | ValueTypeAscription { source: arg, .. }
+            | Loop { body: arg }
+            | Field { lhs: arg, .. }
+            | Repeat { value: arg, .. }
+            | LetBinding { expr: arg, .. } => self.written_places(arg, env, places),
+            Match { scrutinee, arms } => {
+                self.written_places(scrutinee, env, places);
+                for arm in arms {
+                    self.written_places(arm, env, places);
+                }
+            }
+            Arm { guard, body, .. } => {
+                if let Some(guard) = guard {
+                    self.written_places(guard, env, places);
+                }
+                self.written_places(body, env, places);
+            }
+            Block { stmts, expr } => {
+                for stmt in stmts.iter().chain(expr) {
+                    self.written_places(stmt, env, places);
+                }
+            }
+            Break { value, .. } | Return { value } => {
+                if let Some(value) = value {
+                    self.written_places(value, env, places);
+                }
+            }
+            Array { fields } | Tuple { fields } | Closure { upvars: fields, .. } => {
+                for field in fields.iter() {
+                    self.written_places(field, env, places);
+                }
+            }
+            Adt { fields, base, .. } => {
+                for (_, field) in fields.iter() {
+                    self.written_places(field, env, places);
+                }
+                if let Some(base) = base {
+                    self.written_places(base, env, places);
+                }
+            }
+            LetStmt {
+                init, else_block, ..
+            } => {
+                for expr in init.iter().chain(else_block) {
+                    self.written_places(expr, env, places);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The variables and statics underlying the place expression `expr`.
+    fn place_roots(&self, expr: &RExpr<'tcx>, env: &Env<'tcx>, places: &mut Vec<Place<'tcx>>) {
+        use RExprKind::*;
+        match &expr.kind {
+            VarRef { id } | UpvarRef { var_hir_id: id, .. } => places.push(Place::Var(*id)),
+            StaticRef { def_id } => places.push(Place::Static(*def_id)),
+            Deref { arg } if Analyzer::is_mut_ref(arg.ty) || self.is_cell_ref(arg.ty) => {
+                match &arg.kind {
+                    VarRef { id } | UpvarRef { var_hir_id: id, .. } => {
+                        if let Some(place) = env.ref_map.get(id) {
+                            place.roots(places);
+                        }
+                    }
+                    _ => self.place_roots(arg, env, places),
+                }
+            }
+            Deref { arg } | Borrow { arg, .. } => self.place_roots(arg, env, places),
+            Field { lhs, .. } | Index { lhs, .. } => self.place_roots(lhs, env, places),
+            PlaceTypeAscription { source, .. } => self.place_roots(source, env, places),
+            _ => {}
+        }
+    }
+}
+
+impl<'tcx> Place<'tcx> {
+    fn roots(&self, places: &mut Vec<Place<'tcx>>) {
+        match self {
+            Place::Var(_) | Place::Static(_) => places.push(self.clone()),
+            Place::Field { base, .. } | Place::Index { base, .. } => base.roots(places),
+            Place::Ite { then, else_, .. } => {
+                then.roots(places);
+                else_.roots(places);
+            }
+        }
+    }
+}
+
+impl<'tcx> Analyzer<'tcx> {
+    /// The `UnsafeCell` contents of an interior-mutable struct at `place`, e.g. the integer of
+    /// an `AtomicUsize` or the value of a `Cell<T>`, with its type.
+    pub fn interior_place(
+        &self,
+        mut place: Place<'tcx>,
+        mut ty: Ty<'tcx>,
+    ) -> Result<(Place<'tcx>, Ty<'tcx>), AnalysisError> {
+        loop {
+            let TyKind::Adt(adt_def, args) = ty.kind() else {
+                break;
+            };
+            let fields = &adt_def.non_enum_variant().fields;
+            if !adt_def.is_struct() || fields.len() != 1 {
+                break;
+            }
+            let idx = FieldIdx::from_u32(0);
+            let field_ty = fields[idx].ty(self.tcx, args);
+            place = Place::Field {
+                base: Box::new(place),
+                ty,
+                idx,
+            };
+            if adt_def.is_unsafe_cell() {
+                return Ok((place, field_ty));
+            }
+            ty = field_ty;
+        }
+        Err(AnalysisError::Unsupported(format!(
+            "Unsupported interior mutability of {:?}",
+            ty
+        )))
+    }
+}
+
+impl<'tcx> Env<'tcx> {
+    pub fn read_place(&self, place: &Place<'tcx>) -> Result<String, AnalysisError> {
+        match place {
+            Place::Var(id) => Ok(self
+                .env_map
+                .get(id)
+                .ok_or(AnalysisError::Unsupported(format!(
+                    "Variable not found: {:?}",
+                    id
+                )))?
+                .assume
+                .clone()
+                .unwrap_or_default()),
+            Place::Static(def_id) => Ok(self
+                .statics
+                .get(def_id)
+                .ok_or(AnalysisError::Unsupported(format!(
+                    "Static not found: {:?}",
+                    def_id
+                )))?
+                .1
+                .clone()),
+            Place::Field { base, ty, idx } => {
+                let datatype = self.ty_to_sort(*ty)?;
+                Ok(format!(
+                    "({} {})",
+                    Env::selector_name(&datatype, idx.as_usize()),
+                    self.read_place(base)?
+                ))
+            }
+            Place::Index { base, index } => {
+                Ok(format!("(select {} {})", self.read_place(base)?, index))
+            }
+            Place::Ite { cond, then, else_ } => Ok(format!(
+                "(ite {} {} {})",
+                cond,
+                self.read_place(then)?,
+                self.read_place(else_)?
+            )),
+        }
+    }
+
+    pub fn write_place(&mut self, place: &Place<'tcx>, value: String) -> Result<(), AnalysisError> {
+        match place {
+            Place::Var(id) => {
+                let var = self
+                    .env_map
+                    .get_mut(id)
+                    .ok_or(AnalysisError::Unsupported(format!(
+                        "Variable not found: {:?}",
+                        id
+                    )))?;
+                var.assume = Some(value);
+                Ok(())
+            }
+            Place::Static(def_id) => {
+                let (_, current) =
+                    self.statics
+                        .get_mut(def_id)
+                        .ok_or(AnalysisError::Unsupported(format!(
+                            "Static not found: {:?}",
+                            def_id
+                        )))?;
+                *current = value;
+                Ok(())
+            }
+            Place::Field { base, ty, idx } => {
+                let datatype = self.ty_to_sort(*ty)?;
+                let base_value = self.read_place(base)?;
+                let fields = (0..self.field_tys(*ty).len())
+                    .map(|i| {
+                        if i == idx.as_usize() {
+                            value.clone()
+                        } else {
+                            format!("({} {})", Env::selector_name(&datatype, i), base_value)
+                        }
+                    })
+                    .collect::<Vec<String>>();
+                let new_value = format!(
+                    "({} {})",
+                    Env::constructor_name(&datatype),
+                    fields.join(" ")
+                );
+                self.write_place(base, new_value)
+            }
+            Place::Index { base, index } => {
+                let new_value = format!("(store {} {} {})", self.read_place(base)?, index, value);
+                self.write_place(base, new_value)
+            }
+            Place::Ite { cond, then, else_ } => {
+                let then_value = format!("(ite {} {} {})", cond, value, self.read_place(then)?);
+                let else_value = format!("(ite {} {} {})", cond, self.read_place(else_)?, value);
+                self.write_place(then, then_value)?;
+                self.write_place(else_, else_value)
+            }
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use rustc_middle::ty::{GenericArgsRef, Instance, InstanceKind, ParamEnv, TyKind};
 use rustc_span::{
     def_id::{DefId, LocalDefId},
     Span,
@@ -7,6 +8,7 @@ use std::io::Write;
 use std::process::Command;
 
 use crate::analyze::*;
+use crate::thir::generate_rthir_instance;
 
 impl<'tcx> Analyzer<'tcx> {
     pub fn get_fn(&self, id: LocalDefId) -> Result<Rc<RThir<'tcx>>, AnalysisError> {
@@ -29,10 +31,70 @@ impl<'tcx> Analyzer<'tcx> {
         }
     }
 
+    /// Resolves a call to the function that is actually executed; trait methods are
+    /// resolved to the impl selected by the (already substituted) generic arguments.
+    /// The flag is set for calls whose impl is only known at runtime (`dyn Trait`).
+    pub fn resolve_fn(
+        &self,
+        def_id: DefId,
+        args: GenericArgsRef<'tcx>,
+    ) -> (DefId, GenericArgsRef<'tcx>, bool) {
+        // `Fn*` calls on a closure, or on a reference to one, run the closure body.
+        if let Some(trait_id) = self.tcx.trait_of_item(def_id) {
+            if self.tcx.fn_trait_kind_from_def_id(trait_id).is_some() {
+                if let TyKind::Closure(closure_id, closure_args) = args.type_at(0).peel_refs().kind()
+                {
+                    return (*closure_id, closure_args, false);
+                }
+            }
+        }
+        match Instance::resolve(self.tcx, ParamEnv::reveal_all(), def_id, args) {
+            Ok(Some(instance)) => (
+                instance.def_id(),
+                instance.args,
+                matches!(instance.def, InstanceKind::Virtual(..)),
+            ),
+            Ok(None) => (def_id, args, true),
+            Err(_) => (def_id, args, false),
+        }
+    }
+
+    /// Like `get_local_fn`, but reduces a generic function again for the given arguments.
+    pub fn get_local_instance(
+        &self,
+        def_id: &DefId,
+        args: GenericArgsRef<'tcx>,
+    ) -> Result<Option<Rc<RThir<'tcx>>>, AnalysisError> {
+        // Required trait methods have no body.
+        if !def_id.is_local() || !self.fn_map.contains_key(&def_id.expect_local()) {
+            return Ok(None);
+        }
+        if args.is_empty() {
+            return Ok(self.get_local_fn(def_id));
+        }
+        let key = (def_id.expect_local(), args);
+        if let Some(rthir) = self.instances.borrow().get(&key) {
+            return Ok(Some(rthir.clone()));
+        }
+        let rthir = generate_rthir_instance(&self.tcx, key.0, args).map_err(|_| {
+            AnalysisError::Unsupported(format!(
+                "No RThir of {} could be generated",
+                self.tcx.def_path_str_with_args(*def_id, args)
+            ))
+        })?;
+        let rthir = Rc::new(rthir);
+        self.instances.borrow_mut().insert(key, rthir.clone());
+        Ok(Some(rthir))
+    }
+
     pub fn get_fn_info(&self, def_id: &DefId) -> Vec<String> {
-        let def_path = self.tcx.def_path_str(*def_id);
+        Analyzer::split_def_path(&self.tcx.def_path_str(*def_id))
+    }
+
+    /// The segments of a path such as `std::vec::Vec::<T>::len`.
+    pub fn split_def_path(def_path: &str) -> Vec<String> {
         def_path
-            .split(|c| c == ':' || c == '"' || c == '\\') //TODO: check
+            .split([':', '"', '\\'])
             .filter(|s| !s.is_empty())
             .map(|s| s.to_string())
             .collect()
@@ -43,18 +105,4 @@ impl<'tcx> Analyzer<'tcx> {
         span_str = span_str.replace(|c: char| !c.is_alphanumeric(), "_");
         span_str
     }
-
-    pub fn expr_to_var_id(expr: Rc<RExpr<'tcx>>) -> LocalVarId {
-        match &expr.kind {
-            RExprKind::VarRef { id } => id.clone(),
-            RExprKind::Deref { arg } => {
-                if let RExprKind::VarRef { id } = arg.kind {
-                    id
-                } else {
-                    panic!("Deref target is not a variable")
-                }
-            }
-            _ => unreachable!(),
-        }
-    }
 }
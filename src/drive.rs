@@ -3,6 +3,7 @@ use rustc_middle::ty::TyCtxt;
 use crate::analyze::core::*;
 
 use crate::analyze::analyze;
+use crate::analyze::encoding::Encoding;
 use crate::util::get_fn_id_map;
 use std::fs::File;
 use std::io::Result;
@@ -10,6 +11,7 @@ use std::path::PathBuf;
 
 pub struct Options {
     pub output_file: PathBuf,
+    pub encoding: Encoding,
 }
 
 impl Default for Options {
@@ -17,6 +19,7 @@ impl Default for Options {
         // not impremented
         Options {
             output_file: PathBuf::from("output.smt2"),
+            encoding: Encoding::default(),
         }
     }
 }
@@ -26,7 +29,12 @@ pub fn drive_rust_verifier(tcx: TyCtxt, opts: &Options) -> Result<()> {
         let fn_id_map = get_fn_id_map(&tcx);
         println!("Entry function found: {:?}", entry_def_id);
         //output tcx
-        if let Err(error) = analyze(entry_def_id.expect_local(), fn_id_map, tcx) {
+        if let Err(error) = analyze(
+            entry_def_id.expect_local(),
+            fn_id_map,
+            tcx,
+            opts.encoding,
+        ) {
             use AnalysisError::*;
             match error {
                 Unsupported(message) => {
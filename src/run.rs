@@ -6,6 +6,7 @@ use rustc_interface::{
 use rustc_session::config::OptLevel;
 use std::path::PathBuf;
 
+use crate::analyze::encoding::{FloatEncoding, IntEncoding};
 use crate::drive::{drive_rust_verifier, Options};
 
 struct MyCallbacks {
@@ -41,6 +42,10 @@ pub fn run_rust_verifier() {
     while let Some(arg) = args_iter.next() {
         if arg == "-o" {
             opts.output_file = PathBuf::from(args_iter.next().unwrap());
+        } else if arg == "--ieee-float" {
+            opts.encoding.float = FloatEncoding::Ieee;
+        } else if arg == "--bitvector-int" {
+            opts.encoding.int = IntEncoding::BitVector;
         } else {
             args.push(arg);
         }
@@ -1,4 +1,4 @@
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::{GenericArgs, GenericArgsRef, TyCtxt};
 use rustc_span::{def_id::LocalDefId, ErrorGuaranteed};
 
 mod printer;
@@ -11,8 +11,18 @@ use rthir::RThir;
 pub fn generate_rthir<'tcx>(
     tcx: &TyCtxt<'tcx>,
     owner_def: LocalDefId,
+) -> Result<RThir<'tcx>, ErrorGuaranteed> {
+    generate_rthir_instance(tcx, owner_def, GenericArgs::identity_for_item(*tcx, owner_def))
+}
+
+/// Generates the RThir of `owner_def` with its generic parameters replaced by `args`.
+pub fn generate_rthir_instance<'tcx>(
+    tcx: &TyCtxt<'tcx>,
+    owner_def: LocalDefId,
+    args: GenericArgsRef<'tcx>,
 ) -> Result<RThir<'tcx>, ErrorGuaranteed> {
     let (thir, _) = tcx.thir_body(owner_def)?;
-    let thir = thir.steal();
-    Ok(reduce::reduce_thir(thir))
+    // The THIR is not stolen, so that generic functions can be reduced once per instance.
+    let thir = thir.borrow().clone();
+    Ok(reduce::reduce_thir(*tcx, thir, args))
 }
@@ -1,31 +1,48 @@
 // rustc crates
 use rustc_middle::thir::*;
+use rustc_middle::ty::{self, EarlyBinder, GenericArgsRef, ParamEnv, TyCtxt, TypeFoldable};
 use rustc_span::Span;
+use rustc_target::abi::FieldIdx;
 
 use std::rc::Rc;
 
 use crate::thir::rthir::*;
 
-pub fn reduce_thir(thir: Thir) -> RThir {
-    let mut reducer = Reducer::new(thir);
+pub fn reduce_thir<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    thir: Thir<'tcx>,
+    args: GenericArgsRef<'tcx>,
+) -> RThir<'tcx> {
+    let mut reducer = Reducer::new(tcx, thir, args);
     reducer.reduce();
     reducer.reduced_thir
 }
 
-#[derive(Debug)]
 struct Reducer<'tcx> {
+    tcx: TyCtxt<'tcx>,
     thir: Thir<'tcx>,
+    args: GenericArgsRef<'tcx>,
     reduced_thir: RThir<'tcx>,
 }
 
 impl<'tcx> Reducer<'tcx> {
-    fn new(thir: Thir<'tcx>) -> Self {
+    fn new(tcx: TyCtxt<'tcx>, thir: Thir<'tcx>, args: GenericArgsRef<'tcx>) -> Self {
         Self {
+            tcx,
             thir,
+            args,
             reduced_thir: RThir::new(),
         }
     }
 
+    /// Substitutes the generic arguments of the instance being reduced into `value`.
+    fn instantiate<T: TypeFoldable<TyCtxt<'tcx>> + Copy>(&self, value: T) -> T {
+        let value = EarlyBinder::bind(value).instantiate(self.tcx, self.args);
+        self.tcx
+            .try_normalize_erasing_regions(ParamEnv::reveal_all(), value)
+            .unwrap_or(value)
+    }
+
     fn reduce(&mut self) {
         let new_params = self.reduce_params();
         self.reduced_thir.set_params(new_params);
@@ -51,11 +68,12 @@ impl<'tcx> Reducer<'tcx> {
     }
 
     fn reduce_pattern(&self, pat: &Box<Pat<'tcx>>) -> Rc<RExpr<'tcx>> {
-        let Pat { span, kind, .. } = &**pat;
+        let Pat { ty, span, kind } = &**pat;
         Rc::new(RExpr::new(
             RExprKind::Pat {
                 kind: self.reduce_pattern_kind(kind),
             },
+            self.instantiate(*ty),
             *span,
         ))
     }
@@ -68,6 +86,12 @@ impl<'tcx> Reducer<'tcx> {
                 .collect::<Vec<Rc<RExpr<'tcx>>>>()
                 .into_boxed_slice()
         };
+        let field_pats_to_new = |field_pats: &Vec<FieldPat<'tcx>>| {
+            field_pats
+                .iter()
+                .map(|field_pat| (field_pat.field, self.reduce_pattern(&field_pat.pattern)))
+                .collect::<Box<[(FieldIdx, Rc<RExpr<'tcx>>)]>>()
+        };
 
         match pat_kind {
             PatKind::Wild => RPatKind::Wild,
@@ -89,7 +113,7 @@ impl<'tcx> Reducer<'tcx> {
                 name: *name,
                 mode: *mode,
                 var: *var,
-                ty: *ty,
+                ty: self.instantiate(*ty),
                 subpattern: if let Some(pat) = subpattern {
                     Some(self.reduce_pattern(pat))
                 } else {
@@ -97,6 +121,20 @@ impl<'tcx> Reducer<'tcx> {
                 },
                 is_primary: *is_primary,
             },
+            PatKind::Variant {
+                adt_def,
+                args,
+                variant_index,
+                subpatterns,
+            } => RPatKind::Variant {
+                adt_def: *adt_def,
+                args: self.instantiate(*args),
+                variant_index: *variant_index,
+                subpatterns: field_pats_to_new(subpatterns),
+            },
+            PatKind::Leaf { subpatterns } => RPatKind::Leaf {
+                subpatterns: field_pats_to_new(subpatterns),
+            },
             PatKind::Deref { subpattern } => RPatKind::Deref {
                 subpattern: self.reduce_pattern(subpattern),
             },
@@ -107,7 +145,23 @@ impl<'tcx> Reducer<'tcx> {
                 subpattern: self.reduce_pattern(subpattern),
                 mutability: *mutability,
             },
+            PatKind::Constant { value } => RPatKind::Constant {
+                value: self.instantiate(*value),
+            },
+            PatKind::InlineConstant { subpattern, .. } => {
+                self.reduce_pattern_kind(&subpattern.kind)
+            }
             PatKind::Range(patrange) => RPatKind::Range(patrange.clone()),
+            PatKind::Slice { .. } => RPatKind::Slice,
+            PatKind::Array {
+                prefix,
+                slice,
+                suffix,
+            } => RPatKind::Array {
+                prefix: boxed_slice_to_new(prefix),
+                slice: slice.as_ref().map(|pat| self.reduce_pattern(pat)),
+                suffix: boxed_slice_to_new(suffix),
+            },
             PatKind::Or { pats } => RPatKind::Or {
                 pats: boxed_slice_to_new(pats),
             },
@@ -123,7 +177,7 @@ impl<'tcx> Reducer<'tcx> {
     fn reduce_expr(&self, expr_id: &ExprId) -> Rc<RExpr<'tcx>> {
         let expr = &self.thir[*expr_id];
         let rexprkind = self.reduce_expr_kind(&expr.kind);
-        Rc::new(RExpr::new(rexprkind, expr.span))
+        Rc::new(RExpr::new(rexprkind, self.instantiate(expr.ty), expr.span))
     }
 
     fn reduce_expr_kind(&self, expr_kind: &ExprKind<'tcx>) -> RExprKind<'tcx> {
@@ -155,7 +209,7 @@ impl<'tcx> Reducer<'tcx> {
                 from_hir_call,
                 fn_span,
             } => RExprKind::Call {
-                ty: *ty,
+                ty: self.instantiate(*ty),
                 fun: self.reduce_expr(fun),
                 args: args.iter().map(|arg| self.reduce_expr(arg)).collect(),
                 from_hir_call: *from_hir_call,
@@ -182,7 +236,9 @@ impl<'tcx> Reducer<'tcx> {
                 source: self.reduce_expr(source),
             },
             Use { source } => self.handle_use(source),
-            NeverToAny { source } => self.handle_never_to_any(source),
+            NeverToAny { source } => RExprKind::NeverToAny {
+                source: self.reduce_expr(source),
+            },
             PointerCoercion { cast, source } => RExprKind::PointerCoercion {
                 cast: *cast,
                 source: self.reduce_expr(source),
@@ -194,20 +250,12 @@ impl<'tcx> Reducer<'tcx> {
                 expr: self.reduce_expr(expr),
                 pat: self.reduce_pattern(pat),
             },
-            /*
             Match {
                 scrutinee, arms, ..
             } => RExprKind::Match {
                 scrutinee: self.reduce_expr(scrutinee),
-                arms: arms
-                    .iter()
-                    .map(|arm| {
-                        let (arm, span) = self.handle_arm(arm);
-                        Rc::new(RExpr::new(arm, span))
-                    })
-                    .collect(),
+                arms: arms.iter().map(|arm| self.handle_arm(*arm)).collect(),
             },
-            */
             Block { block } => self.handle_block(block),
             Assign { lhs, rhs } => RExprKind::Assign {
                 lhs: self.reduce_expr(lhs),
@@ -261,6 +309,29 @@ impl<'tcx> Reducer<'tcx> {
             Tuple { fields } => RExprKind::Tuple {
                 fields: fields.iter().map(|f| self.reduce_expr(f)).collect(),
             },
+            Adt(box AdtExpr {
+                adt_def,
+                variant_index,
+                args,
+                fields,
+                base,
+                ..
+            }) => RExprKind::Adt {
+                adt_def: *adt_def,
+                variant_index: *variant_index,
+                args: self.instantiate(*args),
+                fields: fields
+                    .iter()
+                    .map(|field| (field.name, self.reduce_expr(&field.expr)))
+                    .collect(),
+                base: base.as_ref().map(|fru| self.reduce_expr(&fru.base)),
+            },
+            Closure(box ClosureExpr {
+                closure_id, upvars, ..
+            }) => RExprKind::Closure {
+                closure_id: *closure_id,
+                upvars: upvars.iter().map(|upvar| self.reduce_expr(upvar)).collect(),
+            },
             PlaceTypeAscription { source, user_ty } => RExprKind::PlaceTypeAscription {
                 source: self.reduce_expr(source),
                 user_ty: user_ty.clone(),
@@ -286,12 +357,28 @@ impl<'tcx> Reducer<'tcx> {
                 user_ty,
             } => RExprKind::NamedConst {
                 def_id: *def_id,
-                args: *args,
+                args: self.instantiate(*args),
                 user_ty: user_ty.clone(),
             },
-            ConstParam { param, def_id } => RExprKind::ConstParam {
-                param: *param,
-                def_id: *def_id,
+            // The parameters of an instance are known. Anonymous constants, e.g. array
+            // lengths, have none of their own.
+            ConstParam { param, def_id } => {
+                match self.args.get(param.index as usize).and_then(|_| {
+                    self.instantiate(ty::Const::new_param(self.tcx, *param))
+                        .try_eval_scalar_int(self.tcx, ParamEnv::reveal_all())
+                }) {
+                    Some((_, lit)) => RExprKind::NonHirLiteral { lit, user_ty: None },
+                    None => RExprKind::ConstParam {
+                        param: *param,
+                        def_id: *def_id,
+                    },
+                }
+            }
+            StaticRef { def_id, .. } => RExprKind::StaticRef { def_id: *def_id },
+            ThreadLocalRef(def_id) => RExprKind::ThreadLocalRef { def_id: *def_id },
+            ConstBlock { did, args } => RExprKind::ConstBlock {
+                did: *did,
+                args: self.instantiate(*args),
             },
             _ => unimplemented!(),
         }
@@ -307,9 +394,24 @@ impl<'tcx> Reducer<'tcx> {
         self.reduce_expr_kind(&use_expr.kind)
     }
 
-    fn handle_never_to_any(&self, expr_id: &ExprId) -> RExprKind<'tcx> {
-        let never_to_any = &self.thir[*expr_id];
-        self.reduce_expr_kind(&never_to_any.kind)
+    fn handle_arm(&self, arm_id: ArmId) -> Rc<RExpr<'tcx>> {
+        let Arm {
+            pattern,
+            guard,
+            body,
+            span,
+            ..
+        } = &self.thir.arms[arm_id];
+        let body = self.reduce_expr(body);
+        Rc::new(RExpr::new(
+            RExprKind::Arm {
+                pattern: self.reduce_pattern(pattern),
+                guard: guard.as_ref().map(|guard| self.reduce_expr(guard)),
+                body: body.clone(),
+            },
+            body.ty,
+            *span,
+        ))
     }
 
     fn handle_block(&self, block_id: &BlockId) -> RExprKind<'tcx> {
@@ -349,11 +451,16 @@ impl<'tcx> Reducer<'tcx> {
                         None
                     },
                     else_block: if let Some(block_id) = else_block {
-                        Some(Rc::new(RExpr::new(self.handle_block(&block_id), *span)))
+                        Some(Rc::new(RExpr::new(
+                            self.handle_block(&block_id),
+                            self.tcx.types.never,
+                            *span,
+                        )))
                     } else {
                         None
                     },
                 },
+                self.tcx.types.unit,
                 *span,
             )),
         }
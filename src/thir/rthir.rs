@@ -1,11 +1,11 @@
 use rustc_hir as hir;
-use rustc_hir::def_id::DefId;
+use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_hir::BindingMode;
 use rustc_middle::middle::region;
-use rustc_middle::mir::{BinOp, BorrowKind, UnOp};
+use rustc_middle::mir::{self, BinOp, BorrowKind, UnOp};
 use rustc_middle::thir::*;
 use rustc_middle::ty::adjustment::PointerCoercion;
-use rustc_middle::ty::{self, CanonicalUserType, GenericArgsRef, Mutability, Ty};
+use rustc_middle::ty::{self, AdtDef, CanonicalUserType, GenericArgsRef, Mutability, Ty};
 use rustc_span::{Span, Symbol};
 use rustc_target::abi::{FieldIdx, VariantIdx};
 
@@ -68,6 +68,15 @@ pub enum RPatKind<'tcx> {
         subpattern: Option<Rc<RExpr<'tcx>>>, //
         is_primary: bool,
     },
+    Variant {
+        adt_def: AdtDef<'tcx>,
+        args: GenericArgsRef<'tcx>,
+        variant_index: VariantIdx,
+        subpatterns: Box<[(FieldIdx, Rc<RExpr<'tcx>>)]>,
+    },
+    Leaf {
+        subpatterns: Box<[(FieldIdx, Rc<RExpr<'tcx>>)]>,
+    },
     Deref {
         subpattern: Rc<RExpr<'tcx>>,
     },
@@ -75,7 +84,17 @@ pub enum RPatKind<'tcx> {
         subpattern: Rc<RExpr<'tcx>>,
         mutability: Mutability,
     },
+    Constant {
+        value: mir::Const<'tcx>,
+    },
     Range(Box<PatRange<'tcx>>),
+    /// A pattern on a slice of unknown length, which is not analyzed.
+    Slice,
+    Array {
+        prefix: Box<[Rc<RExpr<'tcx>>]>,
+        slice: Option<Rc<RExpr<'tcx>>>,
+        suffix: Box<[Rc<RExpr<'tcx>>]>,
+    },
     Or {
         pats: Box<[Rc<RExpr<'tcx>>]>,
     },
@@ -85,12 +104,13 @@ pub enum RPatKind<'tcx> {
 #[derive(Clone, Debug)]
 pub struct RExpr<'tcx> {
     pub kind: RExprKind<'tcx>,
+    pub ty: Ty<'tcx>,
     pub span: Span,
 }
 
 impl<'tcx> RExpr<'tcx> {
-    pub fn new(kind: RExprKind<'tcx>, span: Span) -> Self {
-        Self { kind, span }
+    pub fn new(kind: RExprKind<'tcx>, ty: Ty<'tcx>, span: Span) -> Self {
+        Self { kind, ty, span }
     }
 }
 
@@ -130,6 +150,9 @@ pub enum RExprKind<'tcx> {
     Cast {
         source: Rc<RExpr<'tcx>>,
     },
+    NeverToAny {
+        source: Rc<RExpr<'tcx>>,
+    },
     PointerCoercion {
         cast: PointerCoercion,
         source: Rc<RExpr<'tcx>>,
@@ -148,6 +171,11 @@ pub enum RExprKind<'tcx> {
         scrutinee: Rc<RExpr<'tcx>>,
         arms: Vec<Rc<RExpr<'tcx>>>,
     },
+    Arm {
+        pattern: Rc<RExpr<'tcx>>,
+        guard: Option<Rc<RExpr<'tcx>>>,
+        body: Rc<RExpr<'tcx>>,
+    },
     Block {
         stmts: Vec<Rc<RExpr<'tcx>>>,
         expr: Option<Rc<RExpr<'tcx>>>,
@@ -205,6 +233,17 @@ pub enum RExprKind<'tcx> {
     Tuple {
         fields: Box<[Rc<RExpr<'tcx>>]>,
     },
+    Adt {
+        adt_def: AdtDef<'tcx>,
+        variant_index: VariantIdx,
+        args: GenericArgsRef<'tcx>,
+        fields: Box<[(FieldIdx, Rc<RExpr<'tcx>>)]>,
+        base: Option<Rc<RExpr<'tcx>>>,
+    },
+    Closure {
+        closure_id: LocalDefId,
+        upvars: Box<[Rc<RExpr<'tcx>>]>,
+    },
     PlaceTypeAscription {
         source: Rc<RExpr<'tcx>>,
         user_ty: UserTy<'tcx>,
@@ -233,6 +272,19 @@ pub enum RExprKind<'tcx> {
         param: ty::ParamConst,
         def_id: DefId,
     },
+    /// An inline `const { .. }` block, whose body is a separate anonymous constant.
+    ConstBlock {
+        did: DefId,
+        args: GenericArgsRef<'tcx>,
+    },
+    /// A reference to a `static`; its type is a reference or a raw pointer to the value.
+    StaticRef {
+        def_id: DefId,
+    },
+    /// A reference to a `#[thread_local]` static, e.g. in the expansion of `thread_local!`.
+    ThreadLocalRef {
+        def_id: DefId,
+    },
     LetStmt {
         pattern: Rc<RExpr<'tcx>>,
         init: Option<Rc<RExpr<'tcx>>>,
@@ -10,10 +10,14 @@ use crate::thir::rthir::RThir;
 pub fn get_fn_id_map<'tcx>(tcx: &TyCtxt<'tcx>) -> HashMap<LocalDefId, Rc<RThir<'tcx>>> {
     let mut fn_map: HashMap<LocalDefId, Rc<RThir<'tcx>>> = HashMap::new();
     let fn_keys = tcx.mir_keys(());
-    fn_keys.iter().for_each(|&k| {
-        let rthir = generate_rthir(&tcx, k).expect("Failed to generate rthir");
-        println!("fn_id: {:?}, rthir: {:?}", k, rthir);
-        fn_map.insert(k, Rc::new(rthir));
-    });
+    // Constructors of tuple structs and variants have MIR but no THIR body.
+    fn_keys
+        .iter()
+        .filter(|&&k| !tcx.is_constructor(k.to_def_id()))
+        .for_each(|&k| {
+            let rthir = generate_rthir(&tcx, k).expect("Failed to generate rthir");
+            println!("fn_id: {:?}, rthir: {:?}", k, rthir);
+            fn_map.insert(k, Rc::new(rthir));
+        });
     fn_map
 }
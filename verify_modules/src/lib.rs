@@ -1,5 +1,9 @@
 pub fn Vassert(_: bool) {}
 pub fn Vassume(_: bool) {}
+pub fn Vcover(_: bool) {}
+pub fn Vunreachable() -> ! {
+    unreachable!()
+}
 pub fn Vinvariant(_: bool) {}
 pub fn Vrand_int<T: From<i32>>() -> T {
     T::from(0)
@@ -7,7 +11,13 @@ pub fn Vrand_int<T: From<i32>>() -> T {
 pub fn Vrand_bool<T: From<bool>>() -> T {
     T::from(false)
 }
-pub fn Vrand_float<T: From<f64>>() -> T {
+pub fn Vrand_float<T: From<f32>>() -> T {
     T::from(0.0)
 }
 pub fn Vdrop<T>(_: T) {}
+pub fn Vrequires(_: bool) {}
+pub fn Vensures(_: bool) {}
+pub fn Vextern_spec(_: &str) {}
+pub fn Vresult<T>() -> T {
+    unreachable!()
+}